@@ -0,0 +1,63 @@
+use std::rc::Rc;
+use view_prototype::*;
+use view_prototype::core::*;
+use view_prototype::html::*;
+use view_prototype::css::*;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// DEV - IMPLEMENTATION
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Clone, Hash)]
+pub enum Page {
+    Content,
+    Account
+}
+
+#[derive(Debug, PartialEq, Clone, Hash)]
+pub enum CmsMsg {
+    UrlRequest(Page),
+    UrlChange(Page),
+}
+
+#[derive(Debug, PartialEq, Clone, Hash)]
+pub struct Cms {
+    page: Page,
+}
+
+pub fn init() -> Cms {
+    Cms {page: Page::Content}
+}
+
+pub fn update(counter: &mut Cms, msg: CmsMsg) {
+    // match msg {
+    //     CmsMsg::Increment => {
+    //         counter.value = counter.value + 1;
+    //     }
+    //     CmsMsg::Decrement => {
+    //         counter.value = counter.value - 1;
+    //     }
+    // }
+}
+
+
+pub fn view(counter: &Cms) -> Html<CmsMsg> {view!(
+    display: "flex",
+    flex_direction: "column",
+    nav(ul(
+        li(text("content")),
+        li(text("account"))
+    ))
+)}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// DEV
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn test() {
+    let spec = Component::new(init(), update, view);
+    let process = Process::new(spec);
+    process.start_loop();
+}
@@ -0,0 +1,15 @@
+#![allow(dead_code, unused)]
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+pub mod cms;
+pub mod routes;
+
+#[wasm_bindgen]
+pub fn main() -> Result<(), JsValue> {
+    console_error_panic_hook::set_once();
+    // cms::test();
+    routes::test();
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::rc::Rc;
+use web_sys::console;
+use wasm_bindgen::JsValue;
+use uuid::Uuid;
+use view_prototype::match_path;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// DEV
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Clone, Hash)]
+pub enum Route {
+    RootIndex,
+    ContentIndex,
+    ContentItem {
+        uid: Uuid
+    },
+    AccountIndex,
+    AccountUser {
+        user_name: String
+    },
+    NotFound
+}
+
+pub fn test() {
+    let matcher: Rc<Fn(String)->Option<Route>> = match_path!(
+        [] => {
+            Route::RootIndex
+        }
+        ["content"] => {
+            Route::ContentIndex
+        }
+        ["content", uid: Uuid] => {
+            Route::ContentItem {uid}
+        }
+        ["account"] => {
+            Route::AccountIndex
+        }
+        ["account", user_name: String] => {
+            Route::AccountUser {user_name}
+        }
+        _ => {
+            Route::NotFound
+        }
+    );
+    console::log_1(&JsValue::from(
+        format!("Result: {:#?}", matcher.as_ref()(String::from("/content")))
+    ));
+}
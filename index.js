@@ -1,4 +1,4 @@
-import("./pkg").then(module => {
+import("./demo/pkg").then(module => {
     module.main();
 });
 
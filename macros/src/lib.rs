@@ -0,0 +1,552 @@
+//! JSX-like `html!` for `view-prototype`, expanding to the same `Html`
+//! builder calls as `view!`. Mistakes are reported at the offending token.
+extern crate proc_macro;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{Expr, Ident, Lit, LitStr, Token};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::spanned::Spanned;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// ENTRYPOINT
+///////////////////////////////////////////////////////////////////////////////
+
+/// Builds an `Html` tree:
+///
+/// ```ignore
+/// html! {
+///     <form class="signup" style="display: flex" style:hover:background-color="#eee">
+///         <label for="email">"Email"</label>
+///         <input type="email" required=true @value={model.email.as_str()}
+///             on:input={|_| Msg::Edit} />
+///         <button on:click=[Msg::Save]>"Save"</button>
+///         {footer(model)}
+///     </form>
+/// }
+/// ```
+///
+/// - `name="value"`, `name={expr}` and `name=true` set attributes; names may
+///   be hyphenated (`aria-label`) or prefixed (`xlink:href`);
+/// - `@name={expr}` sets a DOM property;
+/// - `on:event={closure}` adds a handler, `on:event=[msg]` one that always
+///   sends `msg`;
+/// - `style="a: b; c: d"`, `style:property={expr}` and
+///   `style:pseudo-class:property={expr}` add styling;
+/// - `ref={node_ref}`, `on_mount={hook}`, `on_update` and `on_unmount` as
+///   in `view!`;
+/// - children are elements, string literals and `{expr}` nodes.
+///
+/// Several top-level nodes make a fragment.
+#[proc_macro]
+pub fn html(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let nodes = syn::parse_macro_input!(input as Nodes);
+    let output = match nodes.0.len() {
+        1 => nodes.0[0].expand(),
+        _ => {
+            let nodes: Vec<TokenStream> = nodes.0.iter().map(Node::expand).collect();
+            quote!(::view_prototype::html::Html::new_fragment(vec![#(#nodes),*]))
+        }
+    };
+    output.into()
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// SYNTAX
+///////////////////////////////////////////////////////////////////////////////
+
+struct Nodes(Vec<Node>);
+
+enum Node {
+    Element(Element),
+    Text(LitStr),
+    Expr(Expr),
+}
+
+struct Element {
+    name: Name,
+    attributes: Vec<Attribute>,
+    children: Vec<Node>,
+}
+
+/// A tag or attribute name, segments joined by `-` or `:`.
+struct Name {
+    text: String,
+    span: Span,
+}
+
+struct Attribute {
+    property: bool,
+    name: Name,
+    value: Value,
+}
+
+enum Value {
+    Str(LitStr),
+    Bool(bool),
+    Expr(Expr),
+    /// `[msg]`, for `on:` attributes.
+    Message(Expr),
+}
+
+impl Parse for Nodes {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut nodes = Vec::new();
+        while !input.is_empty() {
+            if input.peek(Token![<]) && input.peek2(Token![/]) {
+                let close = input.parse::<Token![<]>()?;
+                return Err(syn::Error::new(close.span(), "closing tag without an opening tag"));
+            }
+            nodes.push(input.parse()?);
+        }
+        if nodes.is_empty() {
+            return Err(input.error("expected at least one node"));
+        }
+        Ok(Nodes(nodes))
+    }
+}
+
+impl Parse for Node {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![<]) {
+            Ok(Node::Element(input.parse()?))
+        } else if input.peek(LitStr) {
+            Ok(Node::Text(input.parse()?))
+        } else if input.peek(syn::token::Brace) {
+            Ok(Node::Expr(braced_expr(input)?))
+        } else {
+            Err(input.error("expected an element, a string literal or a `{...}` expression"))
+        }
+    }
+}
+
+impl Parse for Element {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![<]>()?;
+        let name = Name::parse(input, "a tag name")?;
+        validate_tag(&name)?;
+        let mut attributes: Vec<Attribute> = Vec::new();
+        loop {
+            if input.peek(Token![/]) {
+                input.parse::<Token![/]>()?;
+                input.parse::<Token![>]>()?;
+                return Ok(Element {name, attributes, children: Vec::new()});
+            }
+            if input.peek(Token![>]) {
+                let end = input.parse::<Token![>]>()?;
+                if VOID_ELEMENTS.contains(&name.text.as_str()) {
+                    return Err(syn::Error::new(
+                        end.span(),
+                        format!("`<{}>` is a void element; close it with `/>`", name.text),
+                    ));
+                }
+                break;
+            }
+            if input.is_empty() {
+                return Err(syn::Error::new(name.span, format!("unclosed `<{}`; expected `>` or `/>`", name.text)));
+            }
+            let attribute: Attribute = input.parse()?;
+            if let Some(other) = attributes.iter().find(|x| x.key() == attribute.key()) {
+                return Err(syn::Error::new(
+                    attribute.name.span,
+                    format!("duplicate attribute `{}`", other.name.text),
+                ));
+            }
+            attributes.push(attribute);
+        }
+        let mut children = Vec::new();
+        loop {
+            if input.is_empty() {
+                return Err(syn::Error::new(name.span, format!("unclosed `<{}>`", name.text)));
+            }
+            if input.peek(Token![<]) && input.peek2(Token![/]) {
+                input.parse::<Token![<]>()?;
+                input.parse::<Token![/]>()?;
+                let close = Name::parse(input, "a tag name")?;
+                if close.text != name.text {
+                    return Err(syn::Error::new(
+                        close.span,
+                        format!("expected `</{}>` to close `<{}>`, found `</{}>`", name.text, name.text, close.text),
+                    ));
+                }
+                input.parse::<Token![>]>()?;
+                break;
+            }
+            children.push(input.parse()?);
+        }
+        Ok(Element {name, attributes, children})
+    }
+}
+
+impl Name {
+    fn parse(input: ParseStream, what: &str) -> Result<Self> {
+        let first = match input.call(Ident::parse_any) {
+            Ok(ident) => ident,
+            Err(_) => return Err(input.error(format!("expected {}", what))),
+        };
+        let mut text = first.unraw().to_string();
+        loop {
+            let fork = input.fork();
+            let separator = if fork.parse::<Token![-]>().is_ok() {
+                '-'
+            } else if !fork.peek(Token![::]) && fork.parse::<Token![:]>().is_ok() {
+                ':'
+            } else {
+                break;
+            };
+            if fork.call(Ident::parse_any).is_err() {
+                break;
+            }
+            if separator == '-' {
+                input.parse::<Token![-]>()?;
+            } else {
+                input.parse::<Token![:]>()?;
+            }
+            let segment = input.call(Ident::parse_any)?;
+            text.push(separator);
+            text.push_str(&segment.unraw().to_string());
+        }
+        Ok(Name {text, span: first.span()})
+    }
+}
+
+impl Parse for Attribute {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let property = if input.peek(Token![@]) {
+            input.parse::<Token![@]>()?;
+            true
+        } else {
+            false
+        };
+        let name = Name::parse(input, "an attribute name")?;
+        input.parse::<Token![=]>()?;
+        let value = if input.peek(syn::token::Brace) {
+            Value::Expr(braced_expr(input)?)
+        } else if input.peek(syn::token::Bracket) {
+            let content;
+            syn::bracketed!(content in input);
+            let expr: Expr = content.parse()?;
+            if !content.is_empty() {
+                return Err(content.error("expected `]`"));
+            }
+            Value::Message(expr)
+        } else {
+            match input.parse::<Lit>() {
+                Ok(Lit::Str(lit)) => Value::Str(lit),
+                Ok(Lit::Bool(lit)) => Value::Bool(lit.value),
+                Ok(lit) => return Err(syn::Error::new(lit.span(), "expected a string literal, `true`, `false` or a `{...}` expression")),
+                Err(_) => return Err(input.error("expected a string literal, `true`, `false` or a `{...}` expression")),
+            }
+        };
+        let attribute = Attribute {property, name, value};
+        attribute.validate()?;
+        Ok(attribute)
+    }
+}
+
+/// `{expr}`, holding exactly one expression.
+fn braced_expr(input: ParseStream) -> Result<Expr> {
+    let content;
+    syn::braced!(content in input);
+    let expr: Expr = content.parse()?;
+    if !content.is_empty() {
+        return Err(content.error("expected `}`; a `{...}` holds a single expression"));
+    }
+    Ok(expr)
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// VALIDATION
+///////////////////////////////////////////////////////////////////////////////
+
+const HTML_ELEMENTS: &[&str] = &[
+    "a", "abbr", "address", "area", "article", "aside", "audio", "b", "base", "bdi", "bdo",
+    "blockquote", "body", "br", "button", "canvas", "caption", "cite", "code", "col",
+    "colgroup", "data", "datalist", "dd", "del", "details", "dfn", "dialog", "div", "dl", "dt",
+    "em", "embed", "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3",
+    "h4", "h5", "h6", "head", "header", "hgroup", "hr", "html", "i", "iframe", "img", "input",
+    "ins", "kbd", "label", "legend", "li", "link", "main", "map", "mark", "menu", "meta",
+    "meter", "nav", "noscript", "object", "ol", "optgroup", "option", "output", "p", "param",
+    "picture", "pre", "progress", "q", "rp", "rt", "ruby", "s", "samp", "script", "section",
+    "select", "slot", "small", "source", "span", "strong", "style", "sub", "summary", "sup",
+    "table", "tbody", "td", "template", "textarea", "tfoot", "th", "thead", "time", "title",
+    "tr", "track", "u", "ul", "var", "video", "wbr",
+];
+
+/// Filter primitives (`feBlend`, ...) are accepted by their prefix.
+const SVG_ELEMENTS: &[&str] = &[
+    "svg", "animate", "animateMotion", "animateTransform", "circle", "clipPath", "defs", "desc",
+    "ellipse", "filter", "foreignObject", "g", "image", "line", "linearGradient", "marker",
+    "mask", "metadata", "mpath", "path", "pattern", "polygon", "polyline", "radialGradient",
+    "rect", "set", "stop", "switch", "symbol", "text", "textPath", "tspan", "use", "view",
+];
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param",
+    "source", "track", "wbr",
+];
+
+/// Known elements, and custom elements, which must contain a hyphen.
+fn validate_tag(name: &Name) -> Result<()> {
+    let tag = name.text.as_str();
+    let known = HTML_ELEMENTS.contains(&tag)
+        || SVG_ELEMENTS.contains(&tag)
+        || (tag.starts_with("fe") && tag[2..].starts_with(|c: char| c.is_ascii_uppercase()));
+    let custom = tag.contains('-')
+        && !tag.contains(':')
+        && tag.starts_with(|c: char| c.is_ascii_lowercase())
+        && tag.chars().all(|c| !c.is_ascii_uppercase());
+    if known || custom {
+        Ok(())
+    } else {
+        Err(syn::Error::new(
+            name.span,
+            format!("unknown element `<{}>`; custom elements need a hyphen, e.g. `<my-{}>`", tag, tag.to_lowercase()),
+        ))
+    }
+}
+
+const HOOKS: &[&str] = &["ref", "on_mount", "on_update", "on_unmount"];
+
+/// Known prefixes of namespaced attributes.
+const ATTRIBUTE_PREFIXES: &[&str] = &["xlink", "xml", "xmlns"];
+
+impl Attribute {
+    /// What duplicates are judged by.
+    fn key(&self) -> String {
+        format!("{}{}", if self.property {"@"} else {""}, self.name.text)
+    }
+    fn segments(&self) -> Vec<&str> {
+        self.name.text.split(':').collect()
+    }
+    fn error<T>(&self, message: String) -> Result<T> {
+        Err(syn::Error::new(self.name.span, message))
+    }
+    fn validate(&self) -> Result<()> {
+        let name = self.name.text.as_str();
+        let segments = self.segments();
+        let is_css_name = |x: &str| !x.is_empty() && x.chars().all(|c| c.is_ascii_lowercase() || c == '-');
+        if self.property {
+            if segments.len() > 1 {
+                return self.error(format!("`@{}`: DOM property names have no prefix", name));
+            }
+            return self.check_value(false, "a DOM property");
+        }
+        match segments[0] {
+            "on" => {
+                if segments.len() != 2 || !segments[1].chars().all(|c| c.is_ascii_lowercase()) {
+                    return self.error(format!("`{}`: expected `on:event`, with a lowercase event name", name));
+                }
+                match &self.value {
+                    Value::Expr(_) | Value::Message(_) => Ok(()),
+                    _ => self.error(format!("`{}` takes a `{{closure}}` or a `[message]`", name)),
+                }
+            }
+            "style" if segments.len() == 1 => match &self.value {
+                Value::Str(lit) => parse_declarations(lit).map(|_| ()),
+                _ => self.error(String::from("`style` takes a string literal; use `style:property={expr}` for values")),
+            },
+            "style" => {
+                if segments.len() > 3 || !segments[1..].iter().all(|x| is_css_name(x)) {
+                    return self.error(format!(
+                        "`{}`: expected `style:property` or `style:pseudo-class:property`, in lowercase",
+                        name,
+                    ));
+                }
+                self.check_value(false, "a style")
+            }
+            prefix if segments.len() > 1 && !ATTRIBUTE_PREFIXES.contains(&prefix) => {
+                self.error(format!(
+                    "unknown attribute prefix `{}:`; expected `on:`, `style:`, `xlink:`, `xml:` or `xmlns:`",
+                    prefix,
+                ))
+            }
+            _ if segments.len() > 2 => self.error(format!("`{}`: only one prefix is allowed", name)),
+            "id" => self.error(String::from("ids are generated for each node; use a class or a `data-` attribute")),
+            hook if HOOKS.contains(&hook) => match &self.value {
+                Value::Expr(_) => Ok(()),
+                _ => self.error(format!("`{}` takes a `{{...}}` expression", name)),
+            },
+            _ => self.check_value(true, "an attribute"),
+        }
+    }
+    fn check_value(&self, allow_bool: bool, what: &str) -> Result<()> {
+        match &self.value {
+            Value::Message(expr) => Err(syn::Error::new(
+                expr.span(),
+                format!("`[...]` is for `on:` handlers; {} takes a string or a `{{...}}` expression", what),
+            )),
+            Value::Bool(_) if !allow_bool => self.error(format!("{} takes a string or a `{{...}}` expression", what)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `property: value` pairs of a `style` literal, properties as `view!`
+/// names them (`background_color`).
+fn parse_declarations(lit: &LitStr) -> Result<Vec<(String, String)>> {
+    let mut declarations = Vec::new();
+    for declaration in lit.value().split(';') {
+        if declaration.trim().is_empty() {
+            continue;
+        }
+        match declaration.find(':') {
+            Some(colon) => declarations.push((
+                declaration[..colon].trim().replace("-", "_"),
+                declaration[colon + 1..].trim().to_owned(),
+            )),
+            None => {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    format!("expected `property: value`, found {:?}", declaration.trim()),
+                ));
+            }
+        }
+    }
+    Ok(declarations)
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// EXPANSION
+///////////////////////////////////////////////////////////////////////////////
+
+impl Node {
+    fn expand(&self) -> TokenStream {
+        match self {
+            Node::Element(element) => element.expand(),
+            Node::Text(lit) => quote_spanned! {lit.span()=>
+                ::view_prototype::html::Html::Text {value: ::std::string::String::from(#lit)}
+            },
+            Node::Expr(expr) => quote_spanned!(expr.span()=> #expr),
+        }
+    }
+}
+
+/// `Style::Style` with a value from a `style:` attribute.
+fn style_entry(property: &str, value: &Value) -> TokenStream {
+    let property = property.replace("-", "_");
+    let value = match value {
+        Value::Str(lit) => quote!(::std::string::String::from(#lit)),
+        Value::Expr(expr) => quote_spanned! {expr.span()=>
+            ::view_prototype::css::CssValue::stringify(&(#expr))
+        },
+        _ => unreachable!("checked by validate"),
+    };
+    quote! {
+        ::view_prototype::html::Style::Style {
+            property: ::std::string::String::from(#property),
+            value: #value,
+        }
+    }
+}
+
+impl Element {
+    fn expand(&self) -> TokenStream {
+        let tag = &self.name.text;
+        let mut statements: Vec<TokenStream> = Vec::new();
+        // PSEUDO-CLASS DECLARATIONS ARE GROUPED, WHERE THE FIRST ONE APPEARS
+        let mut pseudo_classes: Vec<(String, Vec<TokenStream>, usize)> = Vec::new();
+        for attribute in &self.attributes {
+            let segments = attribute.segments();
+            let key = attribute.name.text.as_str();
+            let statement = match (&attribute.value, segments.as_slice()) {
+                (Value::Str(lit), _) if attribute.property => quote! {
+                    __html_node.add_attribute(::view_prototype::html::Attribute::Property {
+                        key: ::std::string::String::from(#key),
+                        value: ::view_prototype::html::PropertyValue::from(#lit),
+                    });
+                },
+                (Value::Expr(expr), _) if attribute.property => quote_spanned! {expr.span()=>
+                    __html_node.add_attribute(::view_prototype::html::Attribute::Property {
+                        key: ::std::string::String::from(#key),
+                        value: ::view_prototype::html::PropertyValue::from(#expr),
+                    });
+                },
+                (Value::Expr(expr), ["on", event_name]) => quote_spanned! {expr.span()=>
+                    __html_node.add_event_handler(
+                        ::std::string::String::from(#event_name),
+                        ::std::rc::Rc::new(#expr),
+                    );
+                },
+                (Value::Message(expr), ["on", event_name]) => quote_spanned! {expr.span()=>
+                    __html_node.add_event_message(::std::string::String::from(#event_name), #expr);
+                },
+                (Value::Str(lit), ["style"]) => {
+                    let entries: Vec<TokenStream> = parse_declarations(lit)
+                        .expect("checked by validate")
+                        .into_iter()
+                        .map(|(property, value)| {
+                            style_entry(&property, &Value::Str(LitStr::new(&value, lit.span())))
+                        })
+                        .collect();
+                    quote!(#(__html_node.add_style(#entries);)*)
+                }
+                (value, ["style", property]) => {
+                    let entry = style_entry(property, value);
+                    quote!(__html_node.add_style(#entry);)
+                }
+                (value, ["style", pseudo_class, property]) => {
+                    let entry = style_entry(property, value);
+                    match pseudo_classes.iter_mut().find(|x| x.0 == *pseudo_class) {
+                        Some(group) => group.1.push(entry),
+                        None => pseudo_classes.push((pseudo_class.to_string(), vec![entry], statements.len())),
+                    }
+                    continue;
+                }
+                (Value::Expr(expr), ["ref"]) => quote_spanned! {expr.span()=>
+                    __html_node.set_node_ref(#expr);
+                },
+                (Value::Expr(expr), [hook]) if HOOKS.contains(hook) => {
+                    let setter = Ident::new(&format!("set_{}", hook), attribute.name.span);
+                    quote_spanned! {expr.span()=>
+                        __html_node.#setter(::std::rc::Rc::new(#expr));
+                    }
+                }
+                (Value::Bool(value), _) => quote! {
+                    __html_node.add_attribute(::view_prototype::html::Attribute::Toggle {
+                        key: ::std::string::String::from(#key),
+                        value: #value,
+                    });
+                },
+                (Value::Str(lit), _) => quote! {
+                    __html_node.add_attribute(::view_prototype::html::Attribute::Pair {
+                        key: ::std::string::String::from(#key),
+                        value: ::std::string::String::from(#lit),
+                    });
+                },
+                (Value::Expr(expr), _) => quote_spanned! {expr.span()=>
+                    __html_node.add_attribute(::view_prototype::html::Attribute::Pair {
+                        key: ::std::string::String::from(#key),
+                        value: ::std::string::ToString::to_string(&(#expr)),
+                    });
+                },
+                (Value::Message(_), _) => unreachable!("checked by validate"),
+            };
+            statements.push(statement);
+        }
+        for (name, entries, position) in pseudo_classes.into_iter().rev() {
+            statements.insert(position, quote! {
+                __html_node.add_style(::view_prototype::html::Style::PseudoClass(
+                    ::std::string::String::from(#name),
+                    vec![#(#entries),*],
+                ));
+            });
+        }
+        for child in &self.children {
+            let child = child.expand();
+            statements.push(quote!(__html_node.add_child(#child);));
+        }
+        quote! {{
+            let mut __html_node = ::view_prototype::html::Html::new_node(
+                ::std::string::String::from(#tag)
+            );
+            #(#statements)*
+            __html_node
+        }}
+    }
+}
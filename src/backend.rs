@@ -0,0 +1,327 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::rc::Rc;
+#[cfg(feature = "dom")]
+use wasm_bindgen::JsValue;
+#[cfg(feature = "dom")]
+use wasm_bindgen::closure::Closure;
+
+#[cfg(feature = "dom")]
+use crate::cssom::StyleMount;
+use crate::html::{Mailbox, PropertyValue};
+use crate::platform::Element;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// LIVE NODES
+///////////////////////////////////////////////////////////////////////////////
+
+/// Handle to a node owned by a `Backend`; only that backend can look inside.
+#[derive(Clone)]
+pub struct LiveNode(Rc<Any>);
+
+impl LiveNode {
+    pub fn new<T: Any>(node: T) -> Self {
+        LiveNode(Rc::new(node))
+    }
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref::<T>()
+    }
+}
+
+impl Debug for LiveNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> fmt::Result {
+        write!(f, "LiveNode")
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// BACKEND
+///////////////////////////////////////////////////////////////////////////////
+
+/// Every document operation the vdom performs. `WebBackend` drives the
+/// browser through `web_sys`; `mock_dom::MockDom` keeps an in-memory tree so
+/// diffing and patching can be exercised natively.
+pub trait Backend {
+    // NODES
+    fn create_element(&self, tag: &str, namespace: Option<&str>) -> LiveNode;
+    fn create_text(&self, value: &str) -> LiveNode;
+    fn create_comment(&self, value: &str) -> LiveNode;
+    /// A container whose children move into the parent on insertion.
+    fn create_fragment(&self) -> LiveNode;
+    /// Parses trusted markup into a fragment.
+    fn create_raw(&self, markup: &str) -> LiveNode;
+    fn get_element_by_id(&self, id: &str) -> Option<LiveNode>;
+    fn body(&self) -> LiveNode;
+
+    // ATTRIBUTES & PROPERTIES
+    fn set_attribute(&self, node: &LiveNode, key: &str, value: &str, namespace: Option<&str>);
+    fn remove_attribute(&self, node: &LiveNode, key: &str, namespace: Option<&str>);
+    /// Should leave the property alone if it already holds `value`;
+    /// rewriting e.g. `value` on a focused input resets the cursor.
+    fn set_property(&self, node: &LiveNode, key: &str, value: &PropertyValue);
+
+    // CHILDREN
+    fn append_child(&self, parent: &LiveNode, child: &LiveNode);
+    fn replace_node(&self, old: &LiveNode, new: &LiveNode);
+    fn remove_node(&self, node: &LiveNode);
+    /// Replaces all children with a single text node, or none if empty.
+    fn set_text_content(&self, node: &LiveNode, value: &str);
+
+    // EVENTS - `id` IS THE VNODE ID OF `node`
+    /// Routes `event_name` events on `node` into `mailbox`.
+    fn add_listener(&self, node: &LiveNode, id: &str, event_name: &str, mailbox: &Mailbox);
+    fn remove_listener(&self, node: &LiveNode, id: &str, event_name: &str);
+
+    // STYLES
+    fn insert_rule(&self, rule: &str);
+    /// Drops the rules whose selector mentions `node_id`.
+    fn delete_rules(&self, node_id: &str);
+
+    // WEB INTEROP
+    /// The browser element behind `node`, handed to node refs and lifecycle
+    /// hooks. Backends without a browser return `None` and those are skipped.
+    fn web_element(&self, node: &LiveNode) -> Option<Element> {
+        None
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// WEB-SYS
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "dom")]
+pub struct WebBackend {
+    document: web_sys::Document,
+    style_mount: StyleMount,
+    /// Listener functions by vnode id and event name, kept for removal.
+    listeners: RefCell<HashMap<(String, String), js_sys::Function>>,
+}
+
+#[cfg(feature = "dom")]
+impl WebBackend {
+    pub fn new() -> Self {
+        let document = web_sys::window()
+            .expect("window not available")
+            .document()
+            .expect("document not available");
+        WebBackend {
+            document,
+            style_mount: StyleMount::new(),
+            listeners: RefCell::new(HashMap::new()),
+        }
+    }
+    fn node<'a>(&self, node: &'a LiveNode) -> &'a web_sys::Node {
+        node.downcast_ref::<web_sys::Node>().expect("not a web_sys node")
+    }
+    fn element(&self, node: &LiveNode) -> web_sys::Element {
+        use wasm_bindgen::JsCast;
+        self.node(node).clone().dyn_into().expect("not an element")
+    }
+}
+
+#[cfg(feature = "dom")]
+impl Default for WebBackend {
+    fn default() -> Self {
+        WebBackend::new()
+    }
+}
+
+#[cfg(feature = "dom")]
+impl Debug for WebBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> fmt::Result {
+        write!(f, "WebBackend")
+    }
+}
+
+#[cfg(feature = "dom")]
+impl Backend for WebBackend {
+    fn create_element(&self, tag: &str, namespace: Option<&str>) -> LiveNode {
+        let live: web_sys::Element = match namespace {
+            Some(uri) => self.document.create_element_ns(Some(uri), tag),
+            None => self.document.create_element(tag),
+        }.expect("unable to create element");
+        LiveNode::new(web_sys::Node::from(live))
+    }
+    fn create_text(&self, value: &str) -> LiveNode {
+        LiveNode::new(web_sys::Node::from(self.document.create_text_node(value)))
+    }
+    fn create_comment(&self, value: &str) -> LiveNode {
+        LiveNode::new(web_sys::Node::from(self.document.create_comment(value)))
+    }
+    fn create_fragment(&self) -> LiveNode {
+        LiveNode::new(web_sys::Node::from(self.document.create_document_fragment()))
+    }
+    fn create_raw(&self, markup: &str) -> LiveNode {
+        use wasm_bindgen::JsCast;
+        let template: web_sys::HtmlTemplateElement = self.document
+            .create_element("template")
+            .expect("unable to create element")
+            .dyn_into()
+            .expect("not a template element");
+        template.set_inner_html(markup);
+        LiveNode::new(web_sys::Node::from(template.content()))
+    }
+    fn get_element_by_id(&self, id: &str) -> Option<LiveNode> {
+        self.document
+            .get_element_by_id(id)
+            .map(|live| LiveNode::new(web_sys::Node::from(live)))
+    }
+    fn body(&self) -> LiveNode {
+        let body = self.document.body().expect("document.body not available");
+        LiveNode::new(web_sys::Node::from(body))
+    }
+    fn set_attribute(&self, node: &LiveNode, key: &str, value: &str, namespace: Option<&str>) {
+        let live = self.element(node);
+        match namespace {
+            Some(ns) => live.set_attribute_ns(Some(ns), key, value),
+            None => live.set_attribute(key, value),
+        }.expect("unable to set attribute");
+    }
+    fn remove_attribute(&self, node: &LiveNode, key: &str, namespace: Option<&str>) {
+        let live = self.element(node);
+        match namespace {
+            Some(ns) => live.remove_attribute_ns(Some(ns), key),
+            None => live.remove_attribute(key),
+        }.expect("unable to remove attribute");
+    }
+    fn set_property(&self, node: &LiveNode, key: &str, value: &PropertyValue) {
+        let live = self.node(node);
+        let key = JsValue::from_str(key);
+        let current = js_sys::Reflect::get(live.as_ref(), &key)
+            .ok()
+            .and_then(|x| PropertyValue::from_js(&x));
+        if current.as_ref() != Some(value) {
+            js_sys::Reflect::set(live.as_ref(), &key, &value.to_js())
+                .expect("unable to set property");
+        }
+    }
+    fn append_child(&self, parent: &LiveNode, child: &LiveNode) {
+        self.node(parent)
+            .append_child(self.node(child))
+            .expect("unable to append child");
+    }
+    fn replace_node(&self, old: &LiveNode, new: &LiveNode) {
+        let old = self.node(old);
+        old.parent_node()
+            .expect("missing parent node")
+            .replace_child(self.node(new), old)
+            .expect("unable to replace node");
+    }
+    fn remove_node(&self, node: &LiveNode) {
+        let live = self.node(node);
+        if let Some(parent) = live.parent_node() {
+            parent.remove_child(live).expect("unable to remove node");
+        }
+    }
+    fn set_text_content(&self, node: &LiveNode, value: &str) {
+        let value = if value.is_empty() {None} else {Some(value)};
+        self.node(node).set_text_content(value);
+    }
+    fn add_listener(&self, node: &LiveNode, id: &str, event_name: &str, mailbox: &Mailbox) {
+        use wasm_bindgen::JsCast;
+        let closure: Closure<dyn FnMut(JsValue)> = Closure::wrap(Box::new({
+            let mailbox = mailbox.clone();
+            let event_name = event_name.to_owned();
+            move |value: JsValue| {
+                mailbox.insert(event_name.clone(), value);
+            }
+        }));
+        let function: &js_sys::Function = closure.as_ref().unchecked_ref();
+        let function: js_sys::Function = function.clone();
+        closure.forget();
+        self.node(node)
+            .add_event_listener_with_callback(event_name, &function)
+            .expect("unable to add event handler");
+        self.listeners
+            .borrow_mut()
+            .insert((id.to_owned(), event_name.to_owned()), function);
+    }
+    fn remove_listener(&self, node: &LiveNode, id: &str, event_name: &str) {
+        let key = (id.to_owned(), event_name.to_owned());
+        if let Some(function) = self.listeners.borrow_mut().remove(&key) {
+            self.node(node)
+                .remove_event_listener_with_callback(event_name, &function)
+                .expect("unable to remove event handler");
+        }
+    }
+    fn insert_rule(&self, rule: &str) {
+        self.style_mount.insert(&rule.to_owned());
+    }
+    fn delete_rules(&self, node_id: &str) {
+        self.style_mount.delete(node_id.to_owned());
+    }
+    fn web_element(&self, node: &LiveNode) -> Option<web_sys::Element> {
+        use wasm_bindgen::JsCast;
+        self.node(node).clone().dyn_into().ok()
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// HEADLESS
+///////////////////////////////////////////////////////////////////////////////
+
+/// Keeps no document at all, for running a `Process` whose vnodes are the
+/// only state that matters, e.g. in a `live::LiveServer`. Only the ids it
+/// was given are kept: each resolves, in constant time, to a handle that
+/// every operation ignores, and any other id to `None`. Nodes aren't
+/// tracked, so the ids of removed elements keep resolving.
+#[derive(Debug, Default)]
+pub struct HeadlessBackend {
+    ids: RefCell<HashSet<String>>,
+}
+
+impl HeadlessBackend {
+    pub fn new() -> Self {
+        HeadlessBackend::default()
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn create_element(&self, tag: &str, namespace: Option<&str>) -> LiveNode {
+        LiveNode::new(())
+    }
+    fn create_text(&self, value: &str) -> LiveNode {
+        LiveNode::new(())
+    }
+    fn create_comment(&self, value: &str) -> LiveNode {
+        LiveNode::new(())
+    }
+    fn create_fragment(&self) -> LiveNode {
+        LiveNode::new(())
+    }
+    fn create_raw(&self, markup: &str) -> LiveNode {
+        LiveNode::new(())
+    }
+    fn get_element_by_id(&self, id: &str) -> Option<LiveNode> {
+        if self.ids.borrow().contains(id) {
+            Some(LiveNode::new(()))
+        } else {
+            None
+        }
+    }
+    fn body(&self) -> LiveNode {
+        LiveNode::new(())
+    }
+    fn set_attribute(&self, node: &LiveNode, key: &str, value: &str, namespace: Option<&str>) {
+        if key == "id" {
+            self.ids.borrow_mut().insert(value.to_owned());
+        }
+    }
+    fn remove_attribute(&self, node: &LiveNode, key: &str, namespace: Option<&str>) {}
+    fn set_property(&self, node: &LiveNode, key: &str, value: &PropertyValue) {}
+    fn append_child(&self, parent: &LiveNode, child: &LiveNode) {}
+    fn replace_node(&self, old: &LiveNode, new: &LiveNode) {}
+    fn remove_node(&self, node: &LiveNode) {}
+    fn set_text_content(&self, node: &LiveNode, value: &str) {}
+    fn add_listener(&self, node: &LiveNode, id: &str, event_name: &str, mailbox: &Mailbox) {}
+    fn remove_listener(&self, node: &LiveNode, id: &str, event_name: &str) {}
+    fn insert_rule(&self, rule: &str) {}
+    fn delete_rules(&self, node_id: &str) {}
+}
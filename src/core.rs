@@ -13,15 +13,21 @@ use std::sync::RwLock;
 use std::rc::Rc;
 use either::Either;
 use serde::{self, Serialize, Deserialize};
-use web_sys::console;
-use wasm_bindgen::JsValue;
-use wasm_bindgen::closure;
+#[cfg(feature = "dom")]
 use wasm_bindgen::closure::Closure;
 
 use crate::css;
 use crate::css::CssValue;
-use crate::cssom::*;
 use crate::html::*;
+use crate::platform;
+use crate::platform::JsValue;
+use crate::sync::Patch;
+use crate::backend::{Backend, LiveNode};
+#[cfg(feature = "dom")]
+use crate::backend::WebBackend;
+use crate::debugger::Session;
+use crate::persist::Persistence;
+use crate::hot_reload::HotReload;
 
 
 
@@ -29,25 +35,12 @@ use crate::html::*;
 // INTERNAL UTILS
 ///////////////////////////////////////////////////////////////////////////////
 
-fn mk_raw_view_mount() -> web_sys::Element {
-    let window: web_sys::Window = web_sys::window()
-        .expect("window not available");
-    let document = window
-        .document()
-        .expect("document not available");
-    let body: web_sys::Node = std::convert::From::from(
-        document.body().expect("document.body not available")
-    );
-    let view_mount: web_sys::Node = std::convert::From::from(
-        document.create_element("div").unwrap()
-    );
-    body.append_child(&view_mount);
-    let view_mount = {
-        let view_mount: wasm_bindgen::JsValue = std::convert::From::from(view_mount);
-        let view_mount: web_sys::Element = std::convert::From::from(view_mount);
-        view_mount
-    };
-    view_mount
+/// Wraps a view in a vnode standing for the view mount itself, so views may
+/// return text, comments or fragments and still be patched by their parent.
+fn mk_root_vnode<Msg: Debug + Clone>(view: Html<Msg>) -> Html<Msg> {
+    let mut root = Html::new_node(String::from("div"));
+    root.add_child(view);
+    root
 }
 
 
@@ -61,12 +54,41 @@ where
     Model: Debug + PartialEq + Clone + Hash,
     Msg: Debug + PartialEq + Clone + Hash
 {
-    model: RefCell<Model>,
-    update: Rc<Fn(&mut Model, Msg)>,
-    view: Rc<Fn(&Model)->Html<Msg>>,
+    pub(crate) model: RefCell<Model>,
+    pub(crate) update: Rc<Fn(&mut Model, Msg)>,
+    pub(crate) view: Rc<Fn(&Model)->Html<Msg>>,
 }
 
 
+impl<Model, Msg> Component<Model, Msg>
+where
+    Model: Debug + PartialEq + Clone + Hash,
+    Msg: Debug + PartialEq + Clone + Hash
+{
+    pub fn new<U, V>(init: Model, update: U, view: V) -> Self
+    where
+        U: Fn(&mut Model, Msg) + 'static,
+        V: Fn(&Model)->Html<Msg> + 'static
+    {
+        Component {
+            model: RefCell::new(init),
+            update: Rc::new(update),
+            view: Rc::new(view),
+        }
+    }
+}
+
+/// A stage around `update`, e.g. for logging or permission checks. It sees
+/// the model and each message before `update` does, and passes messages on by
+/// calling `next`: once to let one through (as is or transformed), never to
+/// drop it, or several times to emit extra messages.
+pub type Middleware<Model, Msg> = Rc<Fn(&Model, &Msg, &mut FnMut(Msg))>;
+
+/// Sees the patches of each sync and the view they apply to; see
+/// `Process::on_patches`.
+type PatchObserver<Msg> = Rc<Fn(&Html<Msg>, &[Patch<Msg>])>;
+
+
 #[derive(Clone)]
 pub struct Process<Model, Msg>
 where
@@ -75,8 +97,19 @@ where
 {
     spec: Rc<Component<Model, Msg>>,
     active_vnode: Rc<RefCell<Html<Msg>>>,
-    style_mount: StyleMount,
-    view_mount: web_sys::Element,
+    queued_messages: Rc<RefCell<VecDeque<Msg>>>,
+    /// Hash of the model the active vnode was built from.
+    rendered_model: Rc<Cell<u64>>,
+    /// Stages run in registration order before `update`.
+    middleware: Rc<RefCell<Vec<Middleware<Model, Msg>>>>,
+    /// Recorded messages and models, while the time-travel debugger is on.
+    debugger: Rc<RefCell<Option<Session<Model, Msg>>>>,
+    persistence: Rc<RefCell<Option<Persistence<Model>>>>,
+    hot_reload: Rc<RefCell<Option<HotReload<Model, Msg>>>>,
+    /// Called with the patches of each sync, before they are applied.
+    patch_observers: Rc<RefCell<Vec<PatchObserver<Msg>>>>,
+    backend: Rc<Backend>,
+    view_mount: LiveNode,
 }
 
 
@@ -85,52 +118,299 @@ where
     Model: Debug + PartialEq + Clone + Hash + 'static,
     Msg: Debug + PartialEq + Clone + Hash + 'static
 {
+    #[cfg(feature = "dom")]
     pub fn new(spec: Component<Model, Msg>) -> Self {
-        let style_mount = StyleMount::new();
-        let view_mount = mk_raw_view_mount();
-        let active_vnode = spec.view.as_ref()(&spec.model.clone().into_inner());
-        view_mount.set_inner_html(
-            active_vnode.render(&style_mount).as_str()
+        Process::with_backend(spec, Rc::new(WebBackend::new()))
+    }
+    /// Mounts the component through `backend`, e.g. a `mock_dom::MockDom`
+    /// to run it without a browser.
+    pub fn with_backend(spec: Component<Model, Msg>, backend: Rc<Backend>) -> Self {
+        let view_mount = backend.create_element("div", None);
+        backend.append_child(&backend.body(), &view_mount);
+        let rendered_model = calculate_hash(&*spec.model.borrow());
+        let mut active_vnode = mk_root_vnode(
+            spec.view.as_ref()(&spec.model.borrow())
         );
-        active_vnode.attach_event_listeners();
+        active_vnode.force_all();
+        let root_id = active_vnode.id().expect("missing id on root node");
+        backend.set_attribute(&view_mount, "id", root_id.as_str(), None);
+        for child in active_vnode.children() {
+            backend.append_child(&view_mount, &child.to_live(backend.as_ref()));
+        }
+        let mut messages: Vec<Msg> = Vec::new();
+        active_vnode.did_mount(backend.as_ref(), &mut messages);
         Process {
             spec: Rc::new(spec),
             active_vnode: Rc::new(RefCell::new(
                 active_vnode
             )),
-            style_mount: style_mount,
+            queued_messages: Rc::new(RefCell::new(
+                VecDeque::from(messages)
+            )),
+            rendered_model: Rc::new(Cell::new(rendered_model)),
+            middleware: Rc::new(RefCell::new(Vec::new())),
+            debugger: Rc::new(RefCell::new(None)),
+            persistence: Rc::new(RefCell::new(None)),
+            hot_reload: Rc::new(RefCell::new(None)),
+            patch_observers: Rc::new(RefCell::new(Vec::new())),
+            backend,
             view_mount: view_mount,
         }
     }
+    /// A copy of the current model. Models built from the `im` or `rpds`
+    /// collections (re-exported by this crate) share structure, so this is
+    /// cheap regardless of how much they hold.
+    pub fn model(&self) -> Model {
+        self.spec.model.borrow().clone()
+    }
+    /// The mounted view, including the vnode standing for the view mount.
+    pub fn view(&self) -> Html<Msg> {
+        self.active_vnode.borrow().clone()
+    }
     pub fn sync(&self, new: Html<Msg>) {
-        let root_id = self.active_vnode.borrow().id().expect("missing id on root node");
-        self.active_vnode.borrow_mut().sync(
-            &mut new.clone(),
-            root_id,
-            &self.style_mount,
-        );
+        let mut active_vnode = self.active_vnode.borrow_mut();
+        let root_id = active_vnode.id().expect("missing id on root node");
+        let patches = active_vnode.patches(&mut mk_root_vnode(new), root_id);
+        for observer in self.patch_observers.borrow().iter() {
+            observer.as_ref()(&active_vnode, &patches);
+        }
+        let messages = active_vnode.apply_patches(&patches, self.backend.as_ref());
+        self.queued_messages.borrow_mut().extend(messages);
+    }
+    /// Calls `observer` with the patches of every later sync, and the view
+    /// they apply to, e.g. to replay them on a copy of the view elsewhere;
+    /// see `live`.
+    pub fn on_patches<F>(&self, observer: F)
+    where
+        F: Fn(&Html<Msg>, &[Patch<Msg>]) + 'static
+    {
+        self.patch_observers.borrow_mut().push(Rc::new(observer));
+    }
+    /// Queues `msg` for the next `tick`, as if a handler had sent it.
+    pub fn send(&self, msg: Msg) {
+        self.queued_messages.borrow_mut().push_back(msg);
+    }
+    /// Delivers an event to the element with vnode id `id`, the way its
+    /// listener would; the handler runs on the next `tick`. Returns `false`
+    /// when no element has that id.
+    pub fn dispatch(&self, id: &str, event_name: &str, value: JsValue) -> bool {
+        let active_vnode = self.active_vnode.borrow();
+        let node = active_vnode
+            .find_all(|node| node.id().as_deref() == Some(id))
+            .into_iter()
+            .next();
+        match node {
+            Some(Html::Node{mailbox, ..}) => {
+                mailbox.insert(event_name.to_owned(), value);
+                true
+            }
+            _ => false,
+        }
     }
     pub fn tick(&self) {
-        // UPDATE MODEL
+        // UPDATE MODEL - IN PLACE
         let update_model = |msg| {
-            let new_model = {
-                let mut model = self.spec.model.clone().into_inner();
-                self.spec.update.as_ref()(&mut model, msg);
-                model
-            };
-            self.spec.model.replace(new_model);
+            self.spec.update.as_ref()(&mut self.spec.model.borrow_mut(), msg);
         };
-        for msg in self.active_vnode.borrow().tick() {
-            update_model(msg);
+        if let Some(persistence) = self.persistence.borrow().as_ref() {
+            persistence.flush(platform::now(), &self.spec.model.borrow());
+        }
+        let events = self.active_vnode.borrow().tick();
+        self.queued_messages.borrow_mut().extend(events);
+        {
+            let mut debugger = self.debugger.borrow_mut();
+            // A PAST STATE IS SHOWN - MESSAGES WAIT UNTIL THE DEBUGGER RESUMES
+            if debugger.as_ref().map(|session| !session.is_live()).unwrap_or(false) {
+                return;
+            }
+            let queued: Vec<Msg> = self.queued_messages.borrow_mut().drain(..).collect();
+            if queued.is_empty() {
+                return;
+            }
+            for msg in queued {
+                for msg in self.run_middleware(msg) {
+                    match debugger.as_mut() {
+                        Some(session) => {
+                            update_model(msg.clone());
+                            session.record(msg, self.spec.model.borrow().clone());
+                        }
+                        None => update_model(msg),
+                    }
+                }
+            }
+        }
+        if self.render() {
+            if let Some(persistence) = self.persistence.borrow().as_ref() {
+                persistence.changed(platform::now());
+            }
         }
+    }
+    pub fn add_middleware<F>(&self, stage: F)
+    where
+        F: Fn(&Model, &Msg, &mut FnMut(Msg)) + 'static
+    {
+        self.middleware.borrow_mut().push(Rc::new(stage));
+    }
+    /// The messages `update` should see for `msg`, after every stage.
+    fn run_middleware(&self, msg: Msg) -> Vec<Msg> {
+        let model = self.spec.model.borrow();
+        let mut messages = vec![msg];
+        for stage in self.middleware.borrow().iter() {
+            let mut passed: Vec<Msg> = Vec::new();
+            for msg in messages.iter() {
+                stage.as_ref()(&model, msg, &mut |msg| passed.push(msg));
+            }
+            messages = passed;
+        }
+        messages
+    }
+    /// Re-renders the current model through `view` and `sync`, unless it
+    /// hashes the same as the model last rendered; `Hash` must cover every
+    /// field the view reads. Returns whether it was re-rendered.
+    fn render(&self) -> bool {
+        // SKIP VIEW & SYNC WHEN THE MODEL IS UNCHANGED
+        let model_hash = calculate_hash(&*self.spec.model.borrow());
+        if model_hash == self.rendered_model.get() {
+            return false;
+        }
+        self.rendered_model.set(model_hash);
         // INIT & SYNC VIEW
-        let new_view = self.spec.view.as_ref()(&self.spec.model.clone().into_inner());
+        let new_view = self.spec.view.as_ref()(&self.spec.model.borrow());
         self.sync(new_view);
+        true
+    }
+    
+    ///////////////////////////////////////////////////////////////////////////
+    // PERSISTENCE
+    ///////////////////////////////////////////////////////////////////////////
+    /// Restores the model saved by `persistence`, if any, and from then on
+    /// saves the model once it settles after each change. Under
+    /// `start_loop`, a change still settling is saved when the page is
+    /// hidden.
+    pub fn persist(&self, persistence: Persistence<Model>) {
+        self.show(persistence.load());
+        *self.persistence.borrow_mut() = Some(persistence);
+    }
+    /// Saves the model now if a change hasn't settled yet. Returns whether
+    /// it was saved.
+    pub fn save_pending(&self) -> bool {
+        match self.persistence.borrow().as_ref() {
+            Some(persistence) => persistence.flush_pending(&self.spec.model.borrow()),
+            None => false,
+        }
+    }
+    
+    ///////////////////////////////////////////////////////////////////////////
+    // HOT RELOAD
+    ///////////////////////////////////////////////////////////////////////////
+    /// Rehydrates the state saved before the last reload, if it was saved by
+    /// a build with the same model schema, and saves it again whenever the
+    /// page is unloaded. Call `save_hot_state` to save at other times.
+    #[cfg(feature = "dom")]
+    pub fn hot_reload(&self, hot_reload: HotReload<Model, Msg>) {
+        use wasm_bindgen::JsCast;
+        if let Some((model, session)) = hot_reload.restore() {
+            if session.is_some() {
+                *self.debugger.borrow_mut() = session;
+            }
+            self.show(Some(model));
+        }
+        *self.hot_reload.borrow_mut() = Some(hot_reload);
+        let on_unload: Closure<Fn()> = Closure::wrap(Box::new({
+            let process = self.clone();
+            move || process.save_hot_state()
+        }));
+        web_sys::window()
+            .expect("missing window")
+            .add_event_listener_with_callback("pagehide", on_unload.as_ref().unchecked_ref())
+            .expect("unable to add pagehide listener");
+        on_unload.forget();
     }
+    pub fn save_hot_state(&self) {
+        if let Some(hot_reload) = self.hot_reload.borrow().as_ref() {
+            hot_reload.save(&self.spec.model.borrow(), self.debugger.borrow().as_ref());
+        }
+    }
+    
+    ///////////////////////////////////////////////////////////////////////////
+    // TIME-TRAVEL DEBUGGER
+    ///////////////////////////////////////////////////////////////////////////
+    /// Starts recording every message with a snapshot of the resulting model,
+    /// beginning from the current model.
+    pub fn start_debugger(&self) {
+        *self.debugger.borrow_mut() = Some(Session::new(self.model()));
+    }
+    /// Stops recording, returning to the latest state if a past one is shown.
+    pub fn stop_debugger(&self) -> Option<Session<Model, Msg>> {
+        let session = self.debugger.borrow_mut().take();
+        if let Some(session) = &session {
+            if !session.is_live() {
+                self.show(session.model_at(session.last_position()).cloned());
+            }
+        }
+        session
+    }
+    pub fn debug_session(&self) -> Option<Session<Model, Msg>> {
+        self.debugger.borrow().clone()
+    }
+    /// Shows the recorded state at `position`; see `Session`. Returns `false`
+    /// when the debugger is off or the position is out of range.
+    pub fn jump_to(&self, position: usize) -> bool {
+        let model = self.debugger
+            .borrow_mut()
+            .as_mut()
+            .and_then(|session| session.jump(position));
+        let found = model.is_some();
+        self.show(model);
+        found
+    }
+    /// Returns to the latest state, so that the messages queued while a
+    /// past one was shown are handled on the next `tick`.
+    pub fn resume(&self) -> bool {
+        let position = self.debugger.borrow().as_ref().map(|session| session.last_position());
+        match position {
+            Some(position) => self.jump_to(position),
+            None => false,
+        }
+    }
+    pub fn step_back(&self) -> bool {
+        let position = self.debugger.borrow().as_ref().map(|session| session.position);
+        match position {
+            Some(position) if position > 0 => self.jump_to(position - 1),
+            _ => false,
+        }
+    }
+    pub fn step_forward(&self) -> bool {
+        let position = self.debugger.borrow().as_ref().map(|session| session.position);
+        match position {
+            Some(position) => self.jump_to(position + 1),
+            None => false,
+        }
+    }
+    fn show(&self, model: Option<Model>) {
+        if let Some(model) = model {
+            self.spec.model.replace(model);
+            self.render();
+        }
+    }
+    #[cfg(feature = "dom")]
     pub fn start_loop(self) {
         use wasm_bindgen::JsCast;
         let process = self.clone();
         
+        // THE DEBOUNCED SAVE WOULD NEVER COME ONCE THE PAGE IS GONE
+        let on_unload: Closure<Fn()> = Closure::wrap(Box::new({
+            let process = process.clone();
+            move || {
+                process.save_pending();
+            }
+        }));
+        web_sys::window()
+            .expect("missing window")
+            .add_event_listener_with_callback("pagehide", on_unload.as_ref().unchecked_ref())
+            .expect("unable to add pagehide listener");
+        on_unload.forget();
+        
         let process_callback: Rc<RwLock<Closure<Fn()>>> = Rc::new(
             RwLock::new(Closure::wrap(Box::new(|| unimplemented!())))
         );
@@ -162,83 +442,34 @@ where
 
 
 
-///////////////////////////////////////////////////////////////////////////////
-// APPLICATION ENTRYPOINT
-///////////////////////////////////////////////////////////////////////////////
-
-
-
-
-///////////////////////////////////////////////////////////////////////////////
-// DEV - IMPLEMENTATION
-///////////////////////////////////////////////////////////////////////////////
-
-pub mod app {
-    use super::*;
-    #[macro_use]
-    use super::super::*;
-    
-    #[derive(Debug, PartialEq, Clone, Hash)]
-    pub enum Page {
-        Content,
-        Account
-    }
-    
-    #[derive(Debug, PartialEq, Clone, Hash)]
-    pub enum CmsMsg {
-        UrlRequest(Page),
-        UrlChange(Page),
-    }
-    
-    #[derive(Debug, PartialEq, Clone, Hash)]
-    pub struct Cms {
-        page: Page,
-    }
-    
-    pub fn init() -> Cms {
-        Cms {page: Page::Content}
+impl<Model, Msg> Process<Model, Msg>
+where
+    Model: Debug + PartialEq + Clone + Hash + Serialize + serde::de::DeserializeOwned + 'static,
+    Msg: Debug + PartialEq + Clone + Hash + Serialize + serde::de::DeserializeOwned + 'static
+{
+    /// The recorded session as JSON, e.g. to attach to a bug report. Without
+    /// the debugger on, this is an empty session at the current model.
+    pub fn export_session(&self) -> Result<String, serde_json::Error> {
+        match self.debugger.borrow().as_ref() {
+            Some(session) => serde_json::to_string(session),
+            None => serde_json::to_string(&Session::<Model, Msg>::new(self.model())),
+        }
     }
-    
-    pub fn update(counter: &mut Cms, msg: CmsMsg) {
-        // match msg {
-        //     CmsMsg::Increment => {
-        //         counter.value = counter.value + 1;
-        //     }
-        //     CmsMsg::Decrement => {
-        //         counter.value = counter.value - 1;
-        //     }
-        // }
+    /// Loads an exported session into the debugger and shows the state it
+    /// was exported at. Sessions positioned past their last entry are
+    /// rejected.
+    pub fn import_session(&self, json: &str) -> Result<(), serde_json::Error> {
+        let session: Session<Model, Msg> = serde_json::from_str(json)?;
+        let position = session.position;
+        if position > session.last_position() {
+            return Err(serde::de::Error::custom(format!(
+                "position {} is past the last entry, {}",
+                position,
+                session.last_position(),
+            )));
+        }
+        *self.debugger.borrow_mut() = Some(session);
+        self.jump_to(position);
+        Ok(())
     }
-    
-    
-    pub fn view(counter: &Cms) -> Html<CmsMsg> {view!(
-        display: "flex",
-        flex_direction: "column",
-        nav(ul(
-            li(text("content")),
-            li(text("account"))
-        ))
-    )}
-}
-
-
-
-
-///////////////////////////////////////////////////////////////////////////////
-// DEV
-///////////////////////////////////////////////////////////////////////////////
-
-
-pub fn test() {
-    let spec = Component {
-        model: RefCell::new(
-            app::init()
-        ),
-        update: Rc::new(app::update),
-        view: Rc::new(app::view),
-    };
-    let process = Process::new(spec);
-    process.start_loop();
 }
-
-
@@ -10,10 +10,6 @@ use std::cell::{self, Cell, RefCell};
 use std::rc::Rc;
 use either::Either;
 use serde::{self, Serialize, Deserialize};
-use web_sys::console;
-use wasm_bindgen::JsValue;
-use wasm_bindgen::closure;
-use wasm_bindgen::closure::Closure;
 
 
 
@@ -18,6 +18,7 @@ use wasm_bindgen::closure::Closure;
 
 use crate::css;
 use crate::css::CssValue;
+use crate::html::Style;
 
 
 #[derive(Debug, Clone)]
@@ -39,12 +40,12 @@ impl StyleMount {
         );
         let rules: web_sys::CssStyleSheet = std::convert::From::from(rules);
         let rule_list: web_sys::CssRuleList = rules.css_rules().expect("missing cssRules property");
-        for ix in (1..rule_list.length()).map(|x| x - 1).rev() {
+        for ix in (0..rule_list.length()).rev() {
             let rule: web_sys::CssRule = rule_list.item(ix).expect("rule index error");
             let rule: wasm_bindgen::JsValue = std::convert::From::from(rule);
             let rule: web_sys::CssStyleRule = std::convert::From::from(rule);
             let selector = rule.selector_text();
-            if selector.contains(node_id.as_str()) {
+            if Style::is_rule_for(selector.as_str(), node_id.as_str()) {
                 rules.delete_rule(ix).expect("unable to delete css rule");
             }
         }
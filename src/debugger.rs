@@ -0,0 +1,67 @@
+use std::fmt::Debug;
+use serde::{self, Serialize, Deserialize};
+
+
+///////////////////////////////////////////////////////////////////////////////
+// SESSION
+///////////////////////////////////////////////////////////////////////////////
+
+/// One recorded update: the message and the model it produced.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Entry<Model, Msg> {
+    pub msg: Msg,
+    pub model: Model,
+}
+
+/// Every message a `Process` handled since recording began, with a snapshot
+/// of the model after each one. Positions index states rather than entries:
+/// `0` is the initial model and `n` is the model after the `n`th message.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Session<Model, Msg> {
+    pub initial: Model,
+    pub entries: Vec<Entry<Model, Msg>>,
+    pub position: usize,
+}
+
+impl<Model: Clone, Msg: Clone> Session<Model, Msg> {
+    pub fn new(initial: Model) -> Self {
+        Session {
+            initial,
+            entries: Vec::new(),
+            position: 0,
+        }
+    }
+    /// Index of the latest state.
+    pub fn last_position(&self) -> usize {
+        self.entries.len()
+    }
+    /// Whether the session shows the latest state. Otherwise the process
+    /// is paused on a past state and new messages stay queued.
+    pub fn is_live(&self) -> bool {
+        self.position == self.last_position()
+    }
+    pub fn record(&mut self, msg: Msg, model: Model) {
+        self.entries.push(Entry {msg, model});
+        self.position = self.last_position();
+    }
+    pub fn model_at(&self, position: usize) -> Option<&Model> {
+        match position {
+            0 => Some(&self.initial),
+            ix => self.entries.get(ix - 1).map(|entry| &entry.model),
+        }
+    }
+    pub fn msg_at(&self, position: usize) -> Option<&Msg> {
+        match position {
+            0 => None,
+            ix => self.entries.get(ix - 1).map(|entry| &entry.msg),
+        }
+    }
+    /// Moves to `position`, returning the model to show there.
+    pub fn jump(&mut self, position: usize) -> Option<Model> {
+        let model = self.model_at(position).cloned();
+        if model.is_some() {
+            self.position = position;
+        }
+        model
+    }
+}
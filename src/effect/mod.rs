@@ -1 +1,2 @@
-pub mod nav;
\ No newline at end of file
+#[cfg(feature = "router")]
+pub mod nav;
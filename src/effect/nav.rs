@@ -5,9 +5,6 @@ use std::collections::HashMap;
 use std::cell::{self, Cell, RefCell};
 use std::rc::Rc;
 use either::Either;
-use web_sys::console;
-use wasm_bindgen::JsValue;
-use uuid::Uuid;
 
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -78,23 +75,23 @@ impl PathSegment {
 macro_rules! build_patterns {
     // MANY - STATIC PATH
     ($xs:expr; $path:expr, $($rest:tt)*) => {
-        $xs.push(PathSegment::Static($path.to_owned()));
-        build_patterns!($xs; $($rest)*);
+        $xs.push($crate::effect::nav::PathSegment::Static($path.to_owned()));
+        $crate::build_patterns!($xs; $($rest)*);
     };
     // MANY - PARAMETERIZED PATH
     ($xs:expr; $name:ident : $ty:ty, $($rest:tt)*) => {
-        $xs.push(PathSegment::Binder);
-        build_patterns!($xs; $($rest)*);
+        $xs.push($crate::effect::nav::PathSegment::Binder);
+        $crate::build_patterns!($xs; $($rest)*);
     };
     // SINGLE - EMPTY
     ($xs:expr;) => {};
     // SINGLE - PARAMETERIZED PATH
     ($xs:expr; $name:ident : $ty:ty) => {
-        $xs.push(PathSegment::Binder);
+        $xs.push($crate::effect::nav::PathSegment::Binder);
     };
     // SINGLE - STATIC PATH
     ($xs:expr; $path:expr) => {
-        $xs.push(PathSegment::Static($path.to_owned()));
+        $xs.push($crate::effect::nav::PathSegment::Static($path.to_owned()));
     };
 }
 
@@ -113,7 +110,7 @@ macro_rules! init_binders {
             let current_segment = $xs.remove(0).unpack_string().expect("should be a string");
             let result: Option<$ty> = std::str::FromStr::from_str(current_segment.as_str()).ok();
             if let Some($name) = result {
-                init_binders!($xs; $return_value; $body; $($rest)*);
+                $crate::init_binders!($xs; $return_value; $body; $($rest)*);
             }
         }
     };
@@ -121,7 +118,7 @@ macro_rules! init_binders {
     ($xs:expr; $return_value:expr; $body:expr; $other:expr, $($rest:tt)*) => {
         if $xs.len() >= 1 {
             $xs.remove(0);
-            init_binders!($xs; $return_value; $body; $($rest)*);
+            $crate::init_binders!($xs; $return_value; $body; $($rest)*);
         }
     };
     // DONE
@@ -150,7 +147,7 @@ macro_rules! path_entry {
     // INDEX - EMPTY
     ($raw_input:expr; $return_value:expr; [] => $body:expr) => {
         if $return_value.is_none() {
-            let given_route = UrlPath::parse($raw_input.clone());
+            let given_route = $crate::effect::nav::UrlPath::parse($raw_input.clone());
             if given_route.is_index() {
                 $return_value = Some($body);
             }
@@ -159,15 +156,15 @@ macro_rules! path_entry {
     // PATH SEGMENTS
     ($raw_input:expr; $return_value:expr; [$($xs:tt)*] => $body:expr) => {
         if $return_value.is_none() {
-            let given_route = UrlPath::parse($raw_input.clone());
-            let route_pattern: UrlPath = {
-                let mut xs: Vec<PathSegment> = Vec::new();
-                build_patterns!(xs; $($xs)*);
-                UrlPath::from_segs(xs)
+            let given_route = $crate::effect::nav::UrlPath::parse($raw_input.clone());
+            let route_pattern: $crate::effect::nav::UrlPath = {
+                let mut xs: Vec<$crate::effect::nav::PathSegment> = Vec::new();
+                $crate::build_patterns!(xs; $($xs)*);
+                $crate::effect::nav::UrlPath::from_segs(xs)
             };
-            if UrlPath::static_matches(&given_route, &route_pattern) {
-                let mut route: Vec<PathSegment> = given_route.unpack();
-                init_binders!(route; $return_value; $body; $($xs)*);
+            if $crate::effect::nav::UrlPath::static_matches(&given_route, &route_pattern) {
+                let mut route: Vec<$crate::effect::nav::PathSegment> = given_route.unpack();
+                $crate::init_binders!(route; $return_value; $body; $($xs)*);
             }
         }
     };
@@ -186,67 +183,13 @@ macro_rules! path_entry {
 
 #[macro_export]
 macro_rules! match_path {
-    ($($ps:tt => $ex:tt)*) => {Rc::new(
+    ($($ps:tt => $ex:tt)*) => {std::rc::Rc::new(
         move |raw_input: String| {
             let mut result = None;
             {$(
-                path_entry!(raw_input; result; $ps => $ex);
+                $crate::path_entry!(raw_input; result; $ps => $ex);
             )*}
             result
         }
     )};
 }
-
-
-
-
-///////////////////////////////////////////////////////////////////////////////
-// DEV
-///////////////////////////////////////////////////////////////////////////////
-
-pub mod app {
-    use super::*;
-    
-    #[derive(Debug, PartialEq, Clone, Hash)]
-    pub enum Route {
-        RootIndex,
-        ContentIndex,
-        ContentItem {
-            uid: Uuid
-        },
-        AccountIndex,
-        AccountUser {
-            user_name: String
-        },
-        NotFound
-    }
-}
-
-pub fn test() {
-    use app::Route;
-    
-    let matcher: Rc<Fn(String)->Option<app::Route>> = match_path!(
-        [] => {
-            Route::RootIndex
-        }
-        ["content"] => {
-            Route::ContentIndex
-        }
-        ["content", uid: Uuid] => {
-            Route::ContentItem {uid: uid}
-        }
-        ["account"] => {
-            Route::AccountIndex
-        }
-        ["account", user_name: String] => {
-            Route::AccountUser {user_name: user_name}
-        }
-        _ => {
-            Route::NotFound
-        }
-    );
-    console::log_1(&JsValue::from(
-        format!("Result: {:#?}", matcher.as_ref()(String::from("/content")))
-    ));
-}
-
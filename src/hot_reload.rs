@@ -0,0 +1,150 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use serde::{self, Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::debugger::Session;
+use crate::persist::Storage;
+#[cfg(feature = "dom")]
+use crate::persist::SessionStorage;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// SCHEMA FINGERPRINT
+///////////////////////////////////////////////////////////////////////////////
+
+/// Hash of a model's type name and of the shape of its JSON encoding (field
+/// names and value kinds, not values). Computed from the `init` model, it
+/// changes when fields are added, removed, renamed or retyped.
+pub fn fingerprint<Model: Serialize>(init: &Model) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    let mut hasher = DefaultHasher::new();
+    std::any::type_name::<Model>().hash(&mut hasher);
+    if let Ok(value) = serde_json::to_value(init) {
+        hash_shape(&value, &mut hasher);
+    }
+    hasher.finish()
+}
+
+fn hash_shape<H: Hasher>(value: &Value, hasher: &mut H) {
+    match value {
+        Value::Null => "null".hash(hasher),
+        Value::Bool(_) => "bool".hash(hasher),
+        Value::Number(_) => "number".hash(hasher),
+        Value::String(_) => "string".hash(hasher),
+        Value::Array(_) => "array".hash(hasher),
+        Value::Object(fields) => {
+            "object".hash(hasher);
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+            for key in keys {
+                key.hash(hasher);
+                hash_shape(&fields[key], hasher);
+            }
+        }
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// HOT RELOAD
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    fingerprint: u64,
+    model: Value,
+    session: Option<Value>,
+}
+
+/// Turns the model and session into the JSON of a snapshot.
+type Encode<Model, Msg> = Rc<Fn(&Model, Option<&Session<Model, Msg>>)->Option<(Value, Option<Value>)>>;
+/// Reads a snapshot's JSON back, or `None` when it doesn't fit the model.
+type Decode<Model, Msg> = Rc<Fn(Value, Option<Value>)->Option<(Model, Option<Session<Model, Msg>>)>>;
+
+/// Dev-mode state carried across `wasm-pack build` reloads: the model, and
+/// the debugger's message log when `keep_messages` is set. A snapshot is
+/// only restored when its fingerprint matches the running build.
+#[derive(Clone)]
+pub struct HotReload<Model, Msg> {
+    storage: Rc<Storage>,
+    key: String,
+    fingerprint: u64,
+    keep_messages: bool,
+    encode: Encode<Model, Msg>,
+    decode: Decode<Model, Msg>,
+}
+
+impl<Model, Msg> Debug for HotReload<Model, Msg> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> fmt::Result {
+        write!(f, "HotReload({:?}, {:x})", self.key, self.fingerprint)
+    }
+}
+
+impl<Model, Msg> HotReload<Model, Msg> {
+    /// `init` is the model the app starts from, used for the fingerprint.
+    pub fn new<S>(storage: S, namespace: &str, init: &Model) -> Self
+    where
+        S: Storage + 'static,
+        Model: Clone + Serialize + DeserializeOwned + 'static,
+        Msg: Clone + Serialize + DeserializeOwned + 'static,
+    {
+        HotReload {
+            storage: Rc::new(storage),
+            key: format!("{}/hot-reload", namespace),
+            fingerprint: fingerprint(init),
+            keep_messages: false,
+            encode: Rc::new(|model, session| {
+                let model = serde_json::to_value(model).ok()?;
+                let session = match session {
+                    Some(session) => Some(serde_json::to_value(session).ok()?),
+                    None => None,
+                };
+                Some((model, session))
+            }),
+            decode: Rc::new(|model, session| {
+                let model = serde_json::from_value(model).ok()?;
+                let session = session.and_then(|x| serde_json::from_value(x).ok());
+                Some((model, session))
+            }),
+        }
+    }
+    #[cfg(feature = "dom")]
+    pub fn session(namespace: &str, init: &Model) -> Self
+    where
+        Model: Clone + Serialize + DeserializeOwned + 'static,
+        Msg: Clone + Serialize + DeserializeOwned + 'static,
+    {
+        HotReload::new(SessionStorage, namespace, init)
+    }
+    /// Also carry over the time-travel debugger's session, if it is on.
+    pub fn keep_messages(mut self, keep_messages: bool) -> Self {
+        self.keep_messages = keep_messages;
+        self
+    }
+    pub fn save(&self, model: &Model, session: Option<&Session<Model, Msg>>) {
+        let session = session.filter(|_| self.keep_messages);
+        let snapshot = self.encode.as_ref()(model, session).map(|(model, session)| Snapshot {
+            fingerprint: self.fingerprint,
+            model,
+            session,
+        });
+        if let Some(value) = snapshot.and_then(|x| serde_json::to_string(&x).ok()) {
+            self.storage.set(&self.key, &value);
+        }
+    }
+    /// Takes the saved snapshot, if it was saved by a build with the same
+    /// fingerprint. The snapshot is removed either way.
+    pub fn restore(&self) -> Option<(Model, Option<Session<Model, Msg>>)> {
+        let value = self.storage.get(&self.key)?;
+        self.storage.remove(&self.key);
+        let snapshot: Snapshot = serde_json::from_str(&value).ok()?;
+        if snapshot.fingerprint != self.fingerprint {
+            return None;
+        }
+        self.decode.as_ref()(snapshot.model, snapshot.session)
+    }
+}
@@ -9,38 +9,98 @@ use std::collections::BTreeMap;
 use std::collections::VecDeque;
 use std::cell::{self, Cell, RefCell};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use either::Either;
-use serde::{self, Serialize, Deserialize};
-use web_sys::console;
-use wasm_bindgen::JsValue;
-use wasm_bindgen::closure;
-use wasm_bindgen::closure::Closure;
+use serde::{self, Serialize, Serializer, Deserialize, Deserializer};
+use crate::platform::{JsValue, Element};
 
 use crate::css;
 use crate::css::CssValue;
-use crate::cssom::*;
 use crate::sync;
 use crate::sync::Patch;
+use crate::sanitize::Sanitizer;
+use crate::backend::{Backend, LiveNode};
 
 
 ///////////////////////////////////////////////////////////////////////////////
 // INTERNAL UTILS
 ///////////////////////////////////////////////////////////////////////////////
 
-fn calculate_hash<T: Hash>(t: &T) -> u64 {
+pub(crate) fn calculate_hash<T: Hash>(t: &T) -> u64 {
     use std::collections::hash_map::DefaultHasher;
     let mut s = DefaultHasher::new();
     t.hash(&mut s);
     s.finish()
 }
 
+/// For text and attribute values in markup; `Html::Raw` is written as is.
+fn escape_markup(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Ids for element-like vnodes. Sequential rather than random, so they
+/// never collide while both the mounted and the new tree are alive.
+fn next_id() -> String {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    format!("_{}", NEXT_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+
+
+///////////////////////////////////////////////////////////////////////////////
+// NAMESPACES
+///////////////////////////////////////////////////////////////////////////////
+
+pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+pub const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+pub const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+pub const XMLNS_NAMESPACE: &str = "http://www.w3.org/2000/xmlns/";
+
+#[derive(Debug, PartialEq, Clone, Hash, Serialize, Deserialize)]
+pub enum Namespace {
+    Html,
+    Svg,
+}
+
+impl Namespace {
+    pub fn from_tag(tag: &str) -> Self {
+        match tag {
+            "svg" => Namespace::Svg,
+            _ => Namespace::Html,
+        }
+    }
+    pub fn uri(&self) -> Option<&'static str> {
+        match &self {
+            Namespace::Html => None,
+            Namespace::Svg => Some(SVG_NAMESPACE),
+        }
+    }
+    /// The namespace inherited by the children of a `tag` element in this
+    /// namespace. SVG's `foreignObject` switches back to plain HTML.
+    pub fn for_children_of(&self, tag: &str) -> Self {
+        match (&self, tag) {
+            (Namespace::Svg, "foreignObject") => Namespace::Html,
+            _ => self.clone(),
+        }
+    }
+}
 
 
 ///////////////////////////////////////////////////////////////////////////////
 // HTML ATTRIBUTES
 ///////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, PartialEq, Clone, Hash)]
+#[derive(Debug, PartialEq, Clone, Hash, Serialize, Deserialize)]
 pub enum Attribute {
     Pair {
         key: String,
@@ -49,7 +109,13 @@ pub enum Attribute {
     Toggle {
         key: String,
         value: bool,
-    }
+    },
+    /// A DOM property such as `value` or `checked`. Never rendered as markup;
+    /// assigned on the live element instead.
+    Property {
+        key: String,
+        value: PropertyValue,
+    },
 }
 
 impl Attribute {
@@ -63,22 +129,143 @@ impl Attribute {
         match &self {
             Attribute::Pair{key, ..} => key.clone(),
             Attribute::Toggle{key, ..} => key.clone(),
+            Attribute::Property{key, ..} => key.clone(),
         }
     }
     pub fn value(&self) -> Option<String> {
         match &self {
             Attribute::Pair{value, ..} => Some(value.clone()),
             Attribute::Toggle{..} => None,
+            Attribute::Property{..} => None,
+        }
+    }
+    pub fn is_property(&self) -> bool {
+        matches!(self, Attribute::Property{..})
+    }
+    /// Namespace URI for prefixed keys such as `xlink:href`.
+    pub fn namespace(&self) -> Option<&'static str> {
+        let key = self.key();
+        if key == "xmlns" || key.starts_with("xmlns:") {
+            Some(XMLNS_NAMESPACE)
+        } else if key.starts_with("xlink:") {
+            Some(XLINK_NAMESPACE)
+        } else if key.starts_with("xml:") {
+            Some(XML_NAMESPACE)
+        } else {
+            None
+        }
+    }
+    pub fn set_live(&self, backend: &Backend, live: &LiveNode) {
+        match &self {
+            Attribute::Pair{key, value} => {
+                backend.set_attribute(live, key, value, self.namespace());
+            },
+            Attribute::Toggle{key, value: true} => {
+                backend.set_attribute(live, key, "", self.namespace());
+            },
+            Attribute::Toggle{value: false, ..} => {
+                self.remove_live(backend, live);
+            },
+            Attribute::Property{key, value} => {
+                backend.set_property(live, key, value);
+            },
+        }
+    }
+    pub fn remove_live(&self, backend: &Backend, live: &LiveNode) {
+        match &self {
+            Attribute::Property{..} => (),
+            _ => backend.remove_attribute(live, &self.key(), self.namespace()),
+        }
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// DOM PROPERTIES
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum PropertyValue {
+    Bool(bool),
+    String(String),
+    Number(f64),
+}
+
+impl PropertyValue {
+    /// What a property removed from the view is set back to: `false`, `""`
+    /// or `0`, by the type of its last value.
+    pub fn reset(&self) -> PropertyValue {
+        match &self {
+            PropertyValue::Bool(_) => PropertyValue::Bool(false),
+            PropertyValue::String(_) => PropertyValue::String(String::new()),
+            PropertyValue::Number(_) => PropertyValue::Number(0.0),
+        }
+    }
+}
+
+impl PropertyValue {
+    pub fn to_js(&self) -> JsValue {
+        match &self {
+            PropertyValue::Bool(x) => JsValue::from_bool(*x),
+            PropertyValue::String(x) => JsValue::from_str(x.as_str()),
+            PropertyValue::Number(x) => JsValue::from_f64(*x),
+        }
+    }
+    pub fn from_js(value: &JsValue) -> Option<Self> {
+        if let Some(x) = value.as_bool() {
+            Some(PropertyValue::Bool(x))
+        } else if let Some(x) = value.as_string() {
+            Some(PropertyValue::String(x))
+        } else {
+            value.as_f64().map(PropertyValue::Number)
+        }
+    }
+}
+
+impl Hash for PropertyValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match &self {
+            PropertyValue::Bool(x) => x.hash(state),
+            PropertyValue::String(x) => x.hash(state),
+            PropertyValue::Number(x) => x.to_bits().hash(state),
         }
     }
 }
 
+impl From<bool> for PropertyValue {
+    fn from(x: bool) -> Self {PropertyValue::Bool(x)}
+}
+
+impl From<String> for PropertyValue {
+    fn from(x: String) -> Self {PropertyValue::String(x)}
+}
+
+impl From<&str> for PropertyValue {
+    fn from(x: &str) -> Self {PropertyValue::String(x.to_owned())}
+}
+
+impl From<&String> for PropertyValue {
+    fn from(x: &String) -> Self {PropertyValue::String(x.clone())}
+}
+
+impl From<f64> for PropertyValue {
+    fn from(x: f64) -> Self {PropertyValue::Number(x)}
+}
+
+impl From<i32> for PropertyValue {
+    fn from(x: i32) -> Self {PropertyValue::Number(x as f64)}
+}
+
+impl From<u32> for PropertyValue {
+    fn from(x: u32) -> Self {PropertyValue::Number(x as f64)}
+}
+
 
 ///////////////////////////////////////////////////////////////////////////////
 // CSS STYLING
 ///////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, PartialEq, Clone, Hash)]
+#[derive(Debug, PartialEq, Clone, Hash, Serialize, Deserialize)]
 pub enum Style {
     Style {
         property: String,
@@ -115,6 +302,25 @@ impl Style {
             Style::PseudoClass(name, body) => None,
         }
     }
+    pub fn insert_rules(backend: &Backend, node_id: &String, styles: &Vec<Style>) {
+        let selector = format!("#{id}", id=node_id);
+        backend.insert_rule(&Style::render_decls(&selector, styles));
+        for style in styles {
+            if let Some(rule) = style.render_pseudo_selector(node_id) {
+                backend.insert_rule(&rule);
+            }
+        }
+    }
+    /// Whether `rule`, or a rule with this selector, was inserted by
+    /// `insert_rules` for `node_id`. Ids like `_1` and `_12` share a prefix,
+    /// so a plain substring match is not enough.
+    pub fn is_rule_for(rule: &str, node_id: &str) -> bool {
+        let selector = format!("#{}", node_id);
+        rule.starts_with(selector.as_str()) && matches!(
+            rule[selector.len()..].chars().next(),
+            None | Some(' ') | Some(':') | Some('{')
+        )
+    }
     pub fn render_pseudo_selector(&self, node_id: &String) -> Option<String> {
         match &self {
             Style::Style{..} => None,
@@ -140,13 +346,43 @@ impl Style {
 
 #[derive(Clone)]
 pub struct Handler<Msg> {
-    pub fun: Rc<Fn(JsValue)->Msg>,
-    pub js_ref: js_sys::Function,
+    /// `None` for a detached handler; see `Handler::detached`.
+    pub fun: Option<Rc<Fn(JsValue)->Msg>>,
+    /// Set for handlers that ignore the event and always send this message;
+    /// see `Handler::message`.
+    pub message: Option<Msg>,
 }
 
 impl<Msg> Handler<Msg> {
-    pub fn eval(&self, arg: JsValue) -> Msg {
-        self.fun.as_ref()(arg)
+    pub fn new(fun: Rc<Fn(JsValue)->Msg>) -> Self {
+        Handler {fun: Some(fun), message: None}
+    }
+    /// A handler that sends `msg` whatever the event. Unlike a closure, it
+    /// survives serialization.
+    pub fn message(msg: Msg) -> Self
+    where
+        Msg: Clone + 'static
+    {
+        Handler {
+            fun: Some(Rc::new({
+                let msg = msg.clone();
+                move |_| msg.clone()
+            })),
+            message: Some(msg),
+        }
+    }
+    /// What a closure handler deserializes to: its event is still listened
+    /// for, but it sends nothing here. Only the side that built the view can
+    /// handle the event, e.g. a `live::LiveServer`.
+    pub fn detached() -> Self {
+        Handler {fun: None, message: None}
+    }
+    pub fn is_detached(&self) -> bool {
+        self.fun.is_none()
+    }
+    /// The message for this event; `None` when detached.
+    pub fn eval(&self, arg: JsValue) -> Option<Msg> {
+        self.fun.as_ref().map(|fun| fun.as_ref()(arg))
     }
 }
 
@@ -164,6 +400,24 @@ impl<Msg> Hash for Handler<Msg> {
     fn hash<H: Hasher>(&self, state: &mut H) {}
 }
 
+/// Written as the message the handler always sends. Closures can't be
+/// serialized, so closure handlers are written as nothing and come back
+/// detached; see `Handler::detached`.
+impl<Msg: Serialize> Serialize for Handler<Msg> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.message.serialize(serializer)
+    }
+}
+
+impl<'de, Msg: Deserialize<'de> + Clone + 'static> Deserialize<'de> for Handler<Msg> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Option::<Msg>::deserialize(deserializer)? {
+            Some(msg) => Ok(Handler::message(msg)),
+            None => Ok(Handler::detached()),
+        }
+    }
+}
+
 
 ///////////////////////////////////////////////////////////////////////////////
 // MAILBOX
@@ -191,22 +445,327 @@ impl Hash for Mailbox {
     fn hash<H: Hasher>(&self, state: &mut H) {}
 }
 
+impl Default for Mailbox {
+    fn default() -> Self {
+        Mailbox::new()
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// NODE REFERENCES
+///////////////////////////////////////////////////////////////////////////////
+
+/// Handle to the live element behind a vnode. Filled when the node is
+/// mounted and cleared when it is removed.
+#[derive(Clone, Debug)]
+pub struct NodeRef(Rc<RefCell<Option<Element>>>);
+
+impl NodeRef {
+    pub fn new() -> Self {
+        NodeRef(Rc::new(RefCell::new(None)))
+    }
+    pub fn get(&self) -> Option<Element> {
+        self.0.borrow().clone()
+    }
+    pub fn set(&self, live: Option<Element>) {
+        self.0.replace(live);
+    }
+}
+
+impl Default for NodeRef {
+    fn default() -> Self {
+        NodeRef::new()
+    }
+}
+
+impl PartialEq for NodeRef {
+    fn eq(&self, other: &NodeRef) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Hash for NodeRef {
+    fn hash<H: Hasher>(&self, state: &mut H) {}
+}
+
+/// Refs point into a live document, so only a placeholder is written; a
+/// deserialized ref starts out empty.
+impl Serialize for NodeRef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit()
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <()>::deserialize(deserializer)?;
+        Ok(NodeRef::new())
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// LIFECYCLE HOOKS
+///////////////////////////////////////////////////////////////////////////////
+
+/// Runs with the element once it is in the document; see `Lifecycle`.
+pub type HookFn<Msg> = Rc<Fn(&Element)->Option<Msg>>;
+
+#[derive(Clone)]
+pub struct Hook<Msg>(pub HookFn<Msg>);
+
+impl<Msg> Hook<Msg> {
+    pub fn eval(&self, live: &Element) -> Option<Msg> {
+        self.0.as_ref()(live)
+    }
+}
+
+impl<Msg> Debug for Hook<Msg> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> fmt::Result {
+        write!(f, "Hook")
+    }
+}
+
+/// Closures can't be compared; hooks are equal only when they share one.
+impl<Msg> PartialEq for Hook<Msg> {
+    fn eq(&self, other: &Hook<Msg>) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<Msg> Hash for Hook<Msg> {
+    fn hash<H: Hasher>(&self, state: &mut H) {}
+}
+
+/// Callbacks run by the patcher:
+/// - `on_mount` after the element is inserted, children before parents;
+/// - `on_update` after each sync that keeps the element, children before parents;
+/// - `on_unmount` before the element is removed, children before parents.
+#[derive(Clone, Debug)]
+pub struct Lifecycle<Msg> {
+    pub on_mount: Option<Hook<Msg>>,
+    pub on_update: Option<Hook<Msg>>,
+    pub on_unmount: Option<Hook<Msg>>,
+}
+
+impl<Msg> PartialEq for Lifecycle<Msg> {
+    fn eq(&self, other: &Lifecycle<Msg>) -> bool {
+        self.on_mount == other.on_mount
+            && self.on_update == other.on_update
+            && self.on_unmount == other.on_unmount
+    }
+}
+
+impl<Msg> Hash for Lifecycle<Msg> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.on_mount.hash(state);
+        self.on_update.hash(state);
+        self.on_unmount.hash(state);
+    }
+}
+
+impl<Msg> Lifecycle<Msg> {
+    pub fn new() -> Self {
+        Lifecycle {
+            on_mount: None,
+            on_update: None,
+            on_unmount: None,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.on_mount.is_none() && self.on_update.is_none() && self.on_unmount.is_none()
+    }
+    pub fn run_mount(&self, live: &Element) -> Option<Msg> {
+        self.on_mount.as_ref().and_then(|hook| hook.eval(live))
+    }
+    pub fn run_update(&self, live: &Element) -> Option<Msg> {
+        self.on_update.as_ref().and_then(|hook| hook.eval(live))
+    }
+    pub fn run_unmount(&self, live: &Element) -> Option<Msg> {
+        self.on_unmount.as_ref().and_then(|hook| hook.eval(live))
+    }
+}
+
+impl<Msg> Default for Lifecycle<Msg> {
+    fn default() -> Self {
+        Lifecycle::new()
+    }
+}
+
+/// Hooks are closures over live elements and are not written; deserialized
+/// lifecycles are empty.
+impl<Msg> Serialize for Lifecycle<Msg> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit()
+    }
+}
+
+impl<'de, Msg> Deserialize<'de> for Lifecycle<Msg> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <()>::deserialize(deserializer)?;
+        Ok(Lifecycle::new())
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// LAZY SUBTREES
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone)]
+pub struct LazyView<Msg> {
+    pub fun: Rc<Fn()->Html<Msg>>,
+    /// Set when the node is placed under a parent of another namespace
+    /// (e.g. inside `svg`), and applied whenever the view is built.
+    pub namespace: Option<Namespace>,
+}
+
+impl<Msg: Clone + Debug> LazyView<Msg> {
+    pub fn eval(&self) -> Html<Msg> {
+        let mut html = self.fun.as_ref()();
+        if let Some(namespace) = &self.namespace {
+            html.set_namespace(namespace.clone());
+        }
+        html
+    }
+}
+
+impl<Msg> Debug for LazyView<Msg> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> fmt::Result {
+        write!(f, "LazyView")
+    }
+}
+
+/// As with `Hook`, views are equal only when they share a closure.
+impl<Msg> PartialEq for LazyView<Msg> {
+    fn eq(&self, other: &LazyView<Msg>) -> bool {
+        Rc::ptr_eq(&self.fun, &other.fun) && self.namespace == other.namespace
+    }
+}
+
+impl<Msg> Hash for LazyView<Msg> {
+    fn hash<H: Hasher>(&self, state: &mut H) {}
+}
+
+/// A subtree built from `view(&args)` only when `args` hash differently
+/// from the previous render. Use it for rows and panels that rarely change.
+pub fn lazy<Args, Msg>(args: Args, view: fn(&Args)->Html<Msg>) -> Html<Msg>
+where
+    Args: Hash + 'static,
+    Msg: Clone + Debug + 'static,
+{
+    Html::new_lazy(args, view)
+}
+
+/// Lazy nodes are written as their hash and contents, built first if need
+/// be. They come back forced, with a view that returns the same contents.
+fn serialize_lazy<Msg, S>(
+    hash: &u64,
+    view: &LazyView<Msg>,
+    node: &Vec<Html<Msg>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    Msg: Clone + Debug + Serialize,
+    S: Serializer,
+{
+    if node.is_empty() {
+        (hash, vec![view.eval()]).serialize(serializer)
+    } else {
+        (hash, node).serialize(serializer)
+    }
+}
+
+/// The fields of `Html::Lazy`: hash, view and contents.
+type LazyParts<Msg> = (u64, LazyView<Msg>, Vec<Html<Msg>>);
+
+fn deserialize_lazy<'de, Msg, D>(deserializer: D) -> Result<LazyParts<Msg>, D::Error>
+where
+    Msg: Clone + Debug + Deserialize<'de> + 'static,
+    D: Deserializer<'de>,
+{
+    let (hash, node): (u64, Vec<Html<Msg>>) = Deserialize::deserialize(deserializer)?;
+    if node.is_empty() {
+        return Err(serde::de::Error::invalid_length(0, &"a built lazy node"));
+    }
+    let view = LazyView {
+        fun: Rc::new({
+            let node = node.clone();
+            move || node[0].clone()
+        }),
+        namespace: None,
+    };
+    Ok((hash, view, node))
+}
+
 
 ///////////////////////////////////////////////////////////////////////////////
 // VIRTUAL-DOM NODE
 ///////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, PartialEq, Clone, Hash)]
+/// Serializable when `Msg` is; see `Handler` and `NodeRef` for what does
+/// not survive the trip.
+#[derive(Debug, PartialEq, Clone, Hash, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "Msg: Clone + Debug + Serialize",
+    deserialize = "Msg: Clone + Debug + Deserialize<'de> + 'static",
+))]
 pub enum Html<Msg> {
     Node {
         tag: String,
         id: String,
+        namespace: Namespace,
         attributes: Vec<Attribute>,
         styling: Vec<(Style)>,
         events: BTreeMap<String, Handler<Msg>>,
+        #[serde(skip)]
         mailbox: Mailbox,
+        node_ref: Option<NodeRef>,
+        lifecycle: Lifecycle<Msg>,
+        children: Vec<Html<Msg>>,
+    },
+    /// A container for DOM owned by third-party code. Its children are never
+    /// rendered, diffed or patched; only attributes and styling are synced.
+    Unmanaged {
+        tag: String,
+        id: String,
+        namespace: Namespace,
+        attributes: Vec<Attribute>,
+        styling: Vec<(Style)>,
+        node_ref: Option<NodeRef>,
+        lifecycle: Lifecycle<Msg>,
+    },
+    /// Siblings without a wrapping element; flattened into the parent.
+    Fragment {
+        children: Vec<Html<Msg>>,
+    },
+    /// Rendered as `<!--value-->`; an anchor for empty conditional branches.
+    Comment {
+        value: String,
+    },
+    /// Pre-built markup inserted verbatim. See `sanitize::Sanitizer` for
+    /// content that is not trusted.
+    Raw {
+        value: String,
+    },
+    /// Children mounted into a container under the `target` element (created
+    /// at body level when missing) instead of in place. Still diffed, patched
+    /// and ticked as part of the tree that owns it.
+    Portal {
+        target: String,
+        id: String,
         children: Vec<Html<Msg>>,
     },
+    /// Deferred subtree keyed by the hash of its inputs; see `lazy`. `node`
+    /// stays empty until forced, and a new tree's lazy node is only forced
+    /// when its hash differs from the one it replaces.
+    #[serde(serialize_with = "serialize_lazy", deserialize_with = "deserialize_lazy")]
+    Lazy {
+        hash: u64,
+        view: LazyView<Msg>,
+        node: Vec<Html<Msg>>,
+    },
     Text {
         value: String,
     }
@@ -218,16 +777,17 @@ impl<Msg: Clone + Debug> Html<Msg> {
     ///////////////////////////////////////////////////////////////////////////
     fn render_attributes(&self) -> Option<String> {
         match &self {
-            Html::Node{attributes,..} => {
+            Html::Node{attributes,..} | Html::Unmanaged{attributes, ..} => {
                 let attributes: String = attributes
                     .iter()
                     .filter(|atr| atr.key() != "id")
+                    .filter(|atr| !atr.is_property())
                     .map(|atr| {
                         if atr.is_pair() {
                             format!(
                                 "{k}=\"{v}\"",
                                 k=atr.key(),
-                                v=atr.value().unwrap(),
+                                v=escape_markup(&atr.value().unwrap()),
                             )
                         } else {
                             atr.key()
@@ -240,98 +800,204 @@ impl<Msg: Clone + Debug> Html<Msg> {
             _ => None
         }
     }
-    fn render_css(&self, style_mount: &StyleMount) {
-        pub fn default_selector(style_mount: &StyleMount, id: &String, styles: &Vec<Style>) {
-            let class_selector = format!("#{id}", id=id);
-            let rule = Style::render_decls(&class_selector, styles);
-            style_mount.insert(&rule);
-        }
-        pub fn pseudo_selectors(style_mount: &StyleMount, id: &String, styles: &Vec<Style>) {
-            let mut rules: Vec<String> = Vec::new();
-            for style in styles {
-                match style.render_pseudo_selector(id) {
-                    None => (),
-                    Some(rendered) => rules.push(rendered),
-                }
-            }
-            for rule in rules {
-                style_mount.insert(&rule);
+    /// Tag name, id and attributes. Off the browser (no style mount) ids are
+    /// left out, since only the patcher needs them.
+    fn render_start_tag(&self, backend: Option<&Backend>) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if let Html::Node{tag, id, ..} | Html::Unmanaged{tag, id, ..} = &self {
+            parts.push(tag.clone());
+            if let Some(backend) = backend {
+                self.render_css(backend);
+                parts.push(format!("id={}", id));
             }
         }
+        match self.render_attributes() {
+            Some(ref attributes) if !attributes.is_empty() => parts.push(attributes.clone()),
+            _ => (),
+        }
+        parts.join(" ")
+    }
+    fn render_css(&self, backend: &Backend) {
         match &self {
-            Html::Node{styling, id, ..} => {
-                default_selector(style_mount, &id, styling);
-                pseudo_selectors(style_mount, &id, styling);
+            Html::Node{styling, id, ..} | Html::Unmanaged{styling, id, ..} => {
+                Style::insert_rules(backend, id, styling);
             },
             _ => ()
         }
     }
     
-    pub fn attach_event_listeners(&self) {
-        match (self.get_live().as_ref(), &self) {
-            (Some(live), Html::Node{id, children, events, ..}) => {
-                for child in children {
-                    child.attach_event_listeners();
+    pub fn attach_event_listeners(&self, backend: &Backend) {
+        for child in self.children() {
+            child.attach_event_listeners(backend);
+        }
+        if let (Some(live), Html::Node{id, events, mailbox, ..}) = (self.get_live(backend), &self) {
+            for event_name in events.keys() {
+                backend.add_listener(&live, id, event_name, mailbox);
+            }
+        }
+    }
+    /// Everything that follows inserting this subtree into the document.
+    /// Mount hooks run children first, after listeners and refs are in place.
+    pub fn did_mount(&self, backend: &Backend, messages: &mut Vec<Msg>) {
+        self.mount_portals(backend);
+        self.attach_event_listeners(backend);
+        self.sync_properties(backend);
+        self.bind_node_refs(backend);
+        self.run_mount_hooks(backend, messages);
+    }
+    /// Everything that precedes removing this subtree from the document.
+    /// Unmount hooks run children first, while the elements still exist.
+    pub fn will_unmount(&self, backend: &Backend, messages: &mut Vec<Msg>) {
+        self.run_unmount_hooks(backend, messages);
+        self.delete_event_listeners(backend);
+        self.delete_styles(backend);
+        self.unbind_node_refs();
+        self.unmount_portals(backend);
+    }
+    /// Creates the containers of portals in this subtree, outer portals first
+    /// so nested ones find their target.
+    fn mount_portals(&self, backend: &Backend) {
+        if let Html::Portal{target, id, children} = &self {
+            let host = match backend.get_element_by_id(target.as_str()) {
+                Some(host) => host,
+                None => {
+                    let host = backend.create_element("div", None);
+                    backend.set_attribute(&host, "id", target.as_str(), None);
+                    backend.append_child(&backend.body(), &host);
+                    host
                 }
-                for (event_name, event_handler) in events {
-                    let result = live.add_event_listener_with_callback(
-                        event_name,
-                        &event_handler.js_ref,
-                    );
+            };
+            let container = backend.create_element("div", None);
+            backend.set_attribute(&container, "id", id.as_str(), None);
+            for child in children {
+                backend.append_child(&container, &child.to_live(backend));
+            }
+            backend.append_child(&host, &container);
+        }
+        for child in self.children() {
+            child.mount_portals(backend);
+        }
+    }
+    fn unmount_portals(&self, backend: &Backend) {
+        for child in self.children() {
+            child.unmount_portals(backend);
+        }
+        if let Html::Portal{..} = &self {
+            if let Some(container) = self.get_live(backend) {
+                backend.remove_node(&container);
+            }
+        }
+    }
+    /// The browser element behind this node, for refs and hooks.
+    fn get_web_element(&self, backend: &Backend) -> Option<Element> {
+        self.get_live(backend).and_then(|live| backend.web_element(&live))
+    }
+    fn run_mount_hooks(&self, backend: &Backend, messages: &mut Vec<Msg>) {
+        for child in self.children() {
+            child.run_mount_hooks(backend, messages);
+        }
+        match self.lifecycle() {
+            Some(ref lifecycle) if !lifecycle.is_empty() => {
+                if let Some(msg) = self.get_web_element(backend).and_then(|live| lifecycle.run_mount(&live)) {
+                    messages.push(msg);
                 }
             },
-            _ => ()
+            _ => (),
         }
     }
-    pub fn delete_event_listeners(&self) {
-        match &self {
-            Html::Node{children, events, ..} => {
-                // CHILDREN FIRST
-                for child in children {
-                    child.delete_event_listeners();
-                }
-                // CURRENT NODE
-                let live = self.get_live().expect("failed to get live dom ref");
-                let live: web_sys::EventTarget = From::from(live.clone());
-                for (name, handler) in events {
-                    live.remove_event_listener_with_callback(
-                        name.as_str(),
-                        &handler.js_ref
-                    ).expect("unable to remove event handler");
+    fn run_unmount_hooks(&self, backend: &Backend, messages: &mut Vec<Msg>) {
+        for child in self.children() {
+            child.run_unmount_hooks(backend, messages);
+        }
+        match self.lifecycle() {
+            Some(ref lifecycle) if !lifecycle.is_empty() => {
+                if let Some(msg) = self.get_web_element(backend).and_then(|live| lifecycle.run_unmount(&live)) {
+                    messages.push(msg);
                 }
             },
-            Html::Text{..} => (),
+            _ => (),
         }
     }
-    
-    ///////////////////////////////////////////////////////////////////////////
-    // EVENT-SYSTEM
-    ///////////////////////////////////////////////////////////////////////////
-    pub fn tick(&self) -> Vec<Msg> {
-        let mut messages: Vec<Msg> = Vec::new();
-        // CHILDREN FIRST
-        match &self {
-            Html::Text{..} => {}
-            Html::Node{children, ..} => {
-                for child in children {
-                    messages.append(&mut child.tick())
-                }
-            }
+    pub fn bind_node_refs(&self, backend: &Backend) {
+        if let Some(node_ref) = self.node_ref() {
+            node_ref.set(self.get_web_element(backend));
+        }
+        for child in self.children() {
+            child.bind_node_refs(backend);
+        }
+    }
+    pub fn unbind_node_refs(&self) {
+        for child in self.children() {
+            child.unbind_node_refs();
+        }
+        if let Some(node_ref) = self.node_ref() {
+            node_ref.set(None);
+        }
+    }
+    /// Assigns DOM properties for this subtree once it is in the document.
+    pub fn sync_properties(&self, backend: &Backend) {
+        if self.attributes().iter().any(|x| x.is_property()) {
+            let live = self.get_live(backend).expect("failed to get live dom ref");
+            for attribute in self.attributes() {
+                if let Attribute::Property{key, value} = attribute {
+                    backend.set_property(&live, key, value);
+                }
+            }
+        }
+        for child in self.children() {
+            child.sync_properties(backend);
+        }
+    }
+    pub fn delete_event_listeners(&self, backend: &Backend) {
+        // CHILDREN FIRST
+        for child in self.children() {
+            child.delete_event_listeners(backend);
         }
-        // CURRENT
         match &self {
-            Html::Text{..} => (),
+            Html::Node{id, events, ..} => {
+                // CURRENT NODE, UNLESS A SCRIPT ALREADY REMOVED IT
+                if let Some(live) = self.get_live(backend) {
+                    for event_name in events.keys() {
+                        backend.remove_listener(&live, id, event_name);
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+    
+    fn delete_styles(&self, backend: &Backend) {
+        for child in self.children() {
+            child.delete_styles(backend);
+        }
+        if let Some(id) = self.id() {
+            backend.delete_rules(&id);
+        }
+    }
+    
+    ///////////////////////////////////////////////////////////////////////////
+    // EVENT-SYSTEM
+    ///////////////////////////////////////////////////////////////////////////
+    pub fn tick(&self) -> Vec<Msg> {
+        let mut messages: Vec<Msg> = Vec::new();
+        // CHILDREN FIRST
+        for child in self.children() {
+            messages.append(&mut child.tick())
+        }
+        // CURRENT
+        match &self {
             Html::Node{mailbox, ..} => {
                 match mailbox.remove() {
                     None => {},
                     Some((name, value)) => {
                         match self.lookup_handler(&name) {
                             None => (),
-                            Some(handler) => messages.push(handler.eval(value)),
+                            Some(handler) => messages.extend(handler.eval(value)),
                         }
                     }
                 }
             }
+            _ => (),
         }
         messages
     }
@@ -341,19 +1007,36 @@ impl<Msg: Clone + Debug> Html<Msg> {
     fn diff(&self, new: &Html<Msg>, parent_id: String) -> Vec<Patch<Msg>> {
         let mut results: Vec<Patch<Msg>> = Vec::new();
         match (self, new) {
-            (Html::Node{children: cs1, styling: s1, ..}, Html::Node{children: cs2, styling: s2, ..}) => {
-                if cs1.len() == cs2.len() {
-                    let current_id = self.id().expect("should have an id");
-                    for (c1, c2) in cs1.into_iter().zip(cs2.into_iter()) {
-                        results.append(&mut c1.diff(c2, current_id.clone()));
-                    }
-                } else {
-                    results.push(Patch::SetChildren{
-                        id: self.id().expect("missing id"),
-                        value: cs2.clone(),
-                    });
+            (Html::Node{tag: t1, namespace: ns1, ..}, Html::Node{tag: t2, namespace: ns2, ..}) if t1 != t2 || ns1 != ns2 => {
+                results.push(Patch::SetNode{
+                    id: self.id().expect("missing id"),
+                    value: new.clone()
+                });
+            }
+            (Html::Unmanaged{tag: t1, namespace: ns1, ..}, Html::Unmanaged{tag: t2, namespace: ns2, ..}) if t1 != t2 || ns1 != ns2 => {
+                results.push(Patch::SetNode{
+                    id: self.id().expect("missing id"),
+                    value: new.clone()
+                });
+            }
+            (Html::Unmanaged{..}, Html::Unmanaged{..}) => {
+                results.append(&mut self.diff_element(new));
+            }
+            (Html::Node{..}, Html::Node{..}) => {
+                results.append(&mut self.diff_element(new));
+                results.append(&mut self.diff_children(new));
+            }
+            (Html::Portal{..}, Html::Portal{..}) => {
+                results.append(&mut self.diff_children(new));
+            }
+            (Html::Lazy{node: n1, ..}, Html::Lazy{node: n2, ..}) => {
+                // AN UNFORCED NEW NODE MEANS THE INPUTS ARE UNCHANGED
+                if let (Some(c1), Some(c2)) = (n1.first(), n2.first()) {
+                    results.append(&mut c1.diff(c2, parent_id.clone()));
                 }
             }
+            (Html::Comment{..}, Html::Comment{..}) => {}
+            (Html::Raw{..}, Html::Raw{..}) => {}
             (Html::Text{value: v1}, Html::Text{value: v2}) => {
                 if v1 != v2 {
                     results.push(Patch::SetChildText {
@@ -371,37 +1054,426 @@ impl<Msg: Clone + Debug> Html<Msg> {
         }
         results
     }
-    fn apply_diff(&mut self, changes: &Vec<Patch<Msg>>, style_mount: &StyleMount) {
-        let live = self.get_live();
+    fn diff_children(&self, new: &Html<Msg>) -> Vec<Patch<Msg>> {
+        let mut results: Vec<Patch<Msg>> = Vec::new();
+        let current_id = self.id().expect("should have an id");
+        let cs1 = self.flat_children();
+        let cs2 = new.flat_children();
+        let in_place = cs1.len() == cs2.len() && cs1
+            .iter()
+            .zip(cs2.iter())
+            .all(|(c1, c2)| c1.patchable_in_place(c2, cs1.len()));
+        if in_place {
+            for (c1, c2) in cs1.into_iter().zip(cs2) {
+                results.append(&mut c1.diff(c2, current_id.clone()));
+            }
+        } else {
+            results.push(Patch::SetChildren{
+                id: current_id,
+                value: cs2.into_iter().cloned().collect(),
+            });
+        }
+        results
+    }
+    /// Whether a child can be patched where it stands, or whether its parent
+    /// must rebuild its children. Text and comments have no id of their own;
+    /// text is only rewritten through `SetChildText` when it is an only child.
+    fn patchable_in_place(&self, new: &Html<Msg>, siblings: usize) -> bool {
+        match (self, new) {
+            (Html::Node{..}, Html::Node{..}) => true,
+            // KEYED: ANOTHER KEY IS ANOTHER WIDGET, HOWEVER THE SIBLINGS MOVED
+            (Html::Unmanaged{id: i1, ..}, Html::Unmanaged{id: i2, ..}) => i1 == i2,
+            (Html::Text{value: v1}, Html::Text{value: v2}) => v1 == v2 || siblings == 1,
+            (Html::Comment{value: v1}, Html::Comment{value: v2}) => v1 == v2,
+            (Html::Raw{value: v1}, Html::Raw{value: v2}) => v1 == v2,
+            (Html::Portal{target: t1, ..}, Html::Portal{target: t2, ..}) => t1 == t2,
+            (Html::Lazy{node: n1, ..}, Html::Lazy{node: n2, ..}) => {
+                match (n1.first(), n2.first()) {
+                    (_, None) => true,
+                    (Some(c1), Some(c2)) => c1.patchable_in_place(c2, siblings),
+                    (None, Some(_)) => false,
+                }
+            }
+            _ => false,
+        }
+    }
+    /// Patches shared by every element-like node: attributes, styling, refs,
+    /// hooks and live properties.
+    fn diff_element(&self, new: &Html<Msg>) -> Vec<Patch<Msg>> {
+        let mut results: Vec<Patch<Msg>> = Vec::new();
+        let id = self.id().expect("missing id");
+        let markup_attributes = |xs: &[Attribute]| -> Vec<Attribute> {
+            xs.iter().filter(|x| !x.is_property()).cloned().collect()
+        };
+        if markup_attributes(self.attributes()) != markup_attributes(new.attributes()) {
+            results.push(Patch::SetAttributes{
+                id: id.clone(),
+                value: new.attributes().to_vec(),
+            });
+        }
+        if self.styling() != new.styling() {
+            results.push(Patch::SetStyling{
+                id: id.clone(),
+                value: new.styling().to_vec(),
+            });
+        }
+        if self.node_ref() != new.node_ref() {
+            results.push(Patch::SetNodeRef{
+                id: id.clone(),
+                value: new.node_ref(),
+            });
+        }
+        // HANDLERS CAN'T BE COMPARED, SO THE NEW ONES ALWAYS REPLACE THE OLD
+        if let (Html::Node{events: e1, ..}, Html::Node{events: e2, ..}) = (self, new) {
+            if !e1.is_empty() || !e2.is_empty() {
+                results.push(Patch::SetEvents{
+                    id: id.clone(),
+                    value: e2.clone(),
+                });
+            }
+        }
+        if self.lifecycle() != new.lifecycle() {
+            results.push(Patch::SetLifecycle{
+                id: id.clone(),
+                value: new.lifecycle().expect("missing lifecycle"),
+            });
+        }
+        // PROPERTIES - ONLY WHEN CHANGED; REMOVED ONES ARE RESET
+        let properties = |xs: &[Attribute]| -> BTreeMap<String, PropertyValue> {
+            xs.iter().filter_map(|x| match x {
+                Attribute::Property{key, value} => Some((key.clone(), value.clone())),
+                _ => None,
+            }).collect()
+        };
+        let (old_properties, new_properties) = (properties(self.attributes()), properties(new.attributes()));
+        for (key, value) in new_properties.iter() {
+            if old_properties.get(key) != Some(value) {
+                results.push(Patch::SetProperty{
+                    id: id.clone(),
+                    key: key.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+        for (key, value) in old_properties.iter() {
+            if !new_properties.contains_key(key) {
+                results.push(Patch::RemoveProperty{
+                    id: id.clone(),
+                    key: key.clone(),
+                    reset: value.reset(),
+                });
+            }
+        }
+        results
+    }
+    fn apply_diff(&mut self, changes: &Vec<Patch<Msg>>, backend: &Backend, messages: &mut Vec<Msg>) {
+        let live = self.get_live(backend);
         let self_patches = match self.id() {
             None => Vec::new(),
             Some(id) => sync::get_patches_with_id(changes, id)
         };
+        // REPLACE SELF
+        for patch in self_patches.iter() {
+            if let Patch::SetNode{value, ..} = patch {
+                let live = live.expect("unable to get dom ref");
+                self.will_unmount(backend, messages);
+                backend.replace_node(&live, &value.to_live(backend));
+                value.did_mount(backend, messages);
+                *self = value.clone();
+                return;
+            }
+        }
         match self {
-            Html::Node{children, styling, ..} => {
+            Html::Node{id, children, attributes, styling, events, mailbox, node_ref, lifecycle, ..} => {
                 // UPDATE SELF
                 let live = live.expect("unable to get dom ref");
+                let mut replaced_children = false;
                 for patch in self_patches {
                     match patch {
-                        Patch::SetChildText{value, ..} => {
-                            live.set_text_content(Some(value.as_str()));
-                            let mut new_children: Vec<Html<Msg>> = vec![Html::Text{value: value.clone()}];
-                            *children = new_children;
+                        Patch::SetChildText{..} | Patch::SetChildren{..} => {
+                            Html::apply_children_patch(&live, patch, children, backend, messages);
+                            replaced_children = true;
                         },
-                        Patch::SetChildren{value, ..} => {
-                            
+                        Patch::SetEvents{value, ..} => {
+                            for event_name in events.keys().filter(|x| !value.contains_key(*x)) {
+                                backend.remove_listener(&live, id, event_name);
+                            }
+                            for event_name in value.keys().filter(|x| !events.contains_key(*x)) {
+                                backend.add_listener(&live, id, event_name, mailbox);
+                            }
+                            *events = value;
                         },
-                        Patch::SetNode{value, ..} => {
-                            
+                        patch => {
+                            Html::apply_element_patch(
+                                &live, patch, id, attributes, styling, node_ref, lifecycle, backend,
+                            );
                         },
                     }
                 }
-                // UPDATE CHILDREN
-                for child in children {
-                    child.apply_diff(changes, style_mount);
+                // UPDATE CHILDREN - FRESHLY MOUNTED CHILDREN HAVE NOTHING TO PATCH
+                if !replaced_children {
+                    for child in children.iter_mut() {
+                        child.apply_diff(changes, backend, messages);
+                    }
+                }
+                // CURRENT NODE - AFTER ITS CHILDREN
+                let web_element = backend.web_element(&live);
+                if let Some(msg) = web_element.and_then(|live| lifecycle.run_update(&live)) {
+                    messages.push(msg);
                 }
             },
-            Html::Text{value} => ()
+            Html::Unmanaged{id, attributes, styling, node_ref, lifecycle, ..} => {
+                let live = live.expect("unable to get dom ref");
+                for patch in self_patches {
+                    Html::apply_element_patch(
+                        &live, patch, id, attributes, styling, node_ref, lifecycle, backend,
+                    );
+                }
+                let web_element = backend.web_element(&live);
+                if let Some(msg) = web_element.and_then(|live| lifecycle.run_update(&live)) {
+                    messages.push(msg);
+                }
+            },
+            Html::Portal{children, ..} => {
+                let live = live.expect("unable to get dom ref");
+                let mut replaced_children = false;
+                for patch in self_patches {
+                    Html::apply_children_patch(&live, patch, children, backend, messages);
+                    replaced_children = true;
+                }
+                if !replaced_children {
+                    for child in children.iter_mut() {
+                        child.apply_diff(changes, backend, messages);
+                    }
+                }
+            },
+            Html::Fragment{children} | Html::Lazy{node: children, ..} => {
+                for child in children.iter_mut() {
+                    child.apply_diff(changes, backend, messages);
+                }
+            },
+            Html::Comment{..} | Html::Raw{..} | Html::Text{..} => ()
+        }
+    }
+    fn apply_children_patch(
+        live: &LiveNode,
+        patch: Patch<Msg>,
+        children: &mut Vec<Html<Msg>>,
+        backend: &Backend,
+        messages: &mut Vec<Msg>,
+    ) {
+        match patch {
+            Patch::SetChildText{value, ..} => {
+                for child in children.iter() {
+                    child.will_unmount(backend, messages);
+                }
+                backend.set_text_content(live, value.as_str());
+                *children = vec![Html::Text{value}];
+            },
+            Patch::SetChildren{value, ..} => {
+                Html::replace_children(live, children, value, backend, messages);
+            },
+            _ => (),
+        }
+    }
+    // TAKES THE FIELDS `Node` AND `Unmanaged` SHARE, AS `Node` KEEPS ITS
+    // EVENTS AND CHILDREN BORROWED ALONGSIDE
+    #[allow(clippy::too_many_arguments)]
+    fn apply_element_patch(
+        live: &LiveNode,
+        patch: Patch<Msg>,
+        id: &String,
+        attributes: &mut Vec<Attribute>,
+        styling: &mut Vec<Style>,
+        node_ref: &mut Option<NodeRef>,
+        lifecycle: &mut Lifecycle<Msg>,
+        backend: &Backend,
+    ) {
+        match patch {
+            Patch::SetAttributes{value, ..} => {
+                // A KEY NOW SET AS A PROPERTY STILL LOSES ITS MARKUP ATTRIBUTE
+                for old in attributes.iter() {
+                    if value.iter().filter(|x| !x.is_property()).all(|new| new.key() != old.key()) {
+                        old.remove_live(backend, live);
+                    }
+                }
+                for new in value.iter().filter(|x| !x.is_property()) {
+                    new.set_live(backend, live);
+                }
+                *attributes = value;
+            },
+            Patch::SetStyling{value, ..} => {
+                backend.delete_rules(id);
+                *styling = value;
+                Style::insert_rules(backend, id, styling);
+            },
+            Patch::SetProperty{key, value, ..} => {
+                backend.set_property(live, &key, &value);
+                attributes.retain(|x| !(x.is_property() && x.key() == key));
+                attributes.push(Attribute::Property{key, value});
+            },
+            Patch::RemoveProperty{key, reset, ..} => {
+                backend.set_property(live, &key, &reset);
+                attributes.retain(|x| !(x.is_property() && x.key() == key));
+            },
+            Patch::SetNodeRef{value, ..} => {
+                if let Some(old) = node_ref {
+                    old.set(None);
+                }
+                if let Some(new) = &value {
+                    new.set(backend.web_element(live));
+                }
+                *node_ref = value;
+            },
+            Patch::SetLifecycle{value, ..} => {
+                *lifecycle = value;
+            },
+            _ => (),
+        }
+    }
+    /// Swaps out the children of `live`. Unmanaged children whose id and tag
+    /// survive keep their live element, and with it whatever third-party DOM
+    /// they hold.
+    fn replace_children(
+        live: &LiveNode,
+        children: &mut Vec<Html<Msg>>,
+        value: Vec<Html<Msg>>,
+        backend: &Backend,
+        messages: &mut Vec<Msg>,
+    ) {
+        let is_kept = |old: &Html<Msg>| -> bool {
+            match old {
+                Html::Unmanaged{id, tag, namespace, ..} => value.iter().any(|new| match new {
+                    Html::Unmanaged{id: new_id, tag: new_tag, namespace: new_namespace, ..} => {
+                        id == new_id && tag == new_tag && namespace == new_namespace
+                    }
+                    _ => false,
+                }),
+                _ => false,
+            }
+        };
+        let mut kept: HashMap<String, LiveNode> = HashMap::new();
+        for child in children.iter() {
+            if is_kept(child) {
+                let id = child.id().expect("missing id");
+                kept.insert(id, child.get_live(backend).expect("unable to get dom ref"));
+            } else {
+                child.will_unmount(backend, messages);
+            }
+        }
+        backend.set_text_content(live, "");
+        for child in value.iter() {
+            match child.id().and_then(|id| kept.get(&id)) {
+                Some(kept_live) => {
+                    backend.append_child(live, kept_live);
+                },
+                None => {
+                    backend.append_child(live, &child.to_live(backend));
+                },
+            }
+        }
+        let mut old_children: HashMap<String, Html<Msg>> = children
+            .drain(..)
+            .filter_map(|x| x.id().map(|id| (id, x)))
+            .filter(|(id, _)| kept.contains_key(id))
+            .collect();
+        for child in value {
+            match child.id().and_then(|id| old_children.remove(&id)) {
+                Some(mut old) => {
+                    let live = old.get_live(backend).expect("unable to get dom ref");
+                    for patch in old.diff_element(&child) {
+                        if let Html::Unmanaged{id, attributes, styling, node_ref, lifecycle, ..} = &mut old {
+                            Html::apply_element_patch(
+                                &live, patch, id, attributes, styling, node_ref, lifecycle, backend,
+                            );
+                        }
+                    }
+                    children.push(old);
+                },
+                None => {
+                    child.did_mount(backend, messages);
+                    children.push(child);
+                },
+            }
+        }
+    }
+    
+    
+    ///////////////////////////////////////////////////////////////////////////
+    // LAZY NODES
+    ///////////////////////////////////////////////////////////////////////////
+    fn force(&mut self) {
+        if let Html::Lazy{view, node, ..} = self {
+            if node.is_empty() {
+                node.push(view.eval());
+            }
+        }
+    }
+    /// Builds every lazy node in this subtree, e.g. before it is mounted.
+    pub fn force_all(&mut self) {
+        self.force();
+        if let Some(children) = self.children_mut() {
+            for child in children.iter_mut() {
+                child.force_all();
+            }
+        }
+    }
+    /// Walks `new` alongside the mounted tree the same way `diff` will, and
+    /// forces exactly the lazy nodes whose contents `diff` has to look at.
+    /// Unchanged ones stay unforced, which `diff` treats as "skip".
+    fn prepare_lazy(&mut self, new: &mut Html<Msg>) {
+        if let (Html::Lazy{hash: h1, ..}, Html::Lazy{hash: h2, ..}) = (&*self, &*new) {
+            if h1 != h2 {
+                new.force();
+                if let (Html::Lazy{hash: h1, view: v1, node: n1}, Html::Lazy{hash: h2, view: v2, node: n2}) = (self, new) {
+                    if let (Some(c1), Some(c2)) = (n1.first_mut(), n2.first_mut()) {
+                        c1.prepare_lazy(c2);
+                    }
+                    *h1 = *h2;
+                    *v1 = v2.clone();
+                }
+            }
+            return;
+        }
+        let pairable = match (&*self, &*new) {
+            (Html::Node{tag: t1, namespace: ns1, ..}, Html::Node{tag: t2, namespace: ns2, ..}) => {
+                t1 == t2 && ns1 == ns2
+            }
+            (Html::Portal{target: t1, ..}, Html::Portal{target: t2, ..}) => t1 == t2,
+            _ => false,
+        };
+        if !pairable {
+            new.force_all();
+            return;
+        }
+        let mut cs1 = self.flat_children_mut();
+        let mut cs2 = new.flat_children_mut();
+        if cs1.len() != cs2.len() {
+            for c2 in cs2 {
+                c2.force_all();
+            }
+            return;
+        }
+        for (c1, c2) in cs1.iter().zip(cs2.iter_mut()) {
+            if let (Html::Lazy{hash: h1, ..}, Html::Lazy{hash: h2, ..}) = (&**c1, &**c2) {
+                if h1 != h2 {
+                    c2.force();
+                }
+            }
+        }
+        let siblings = cs1.len();
+        let in_place = cs1
+            .iter()
+            .zip(cs2.iter())
+            .all(|(c1, c2)| c1.patchable_in_place(c2, siblings));
+        if in_place {
+            for (c1, c2) in cs1.into_iter().zip(cs2) {
+                c1.prepare_lazy(c2);
+            }
+        } else {
+            for c2 in cs2 {
+                c2.force_all();
+            }
         }
     }
     
@@ -413,11 +1485,25 @@ impl<Msg: Clone + Debug> Html<Msg> {
         &mut self,
         new: &mut Html<Msg>,
         parent_id: String,
-        style_mount: &StyleMount
-    ) {
-        let patches = self.diff(new, parent_id);
+        backend: &Backend
+    ) -> Vec<Msg> {
+        let patches = self.patches(new, parent_id);
         // console::log_1(&JsValue::from(format!("{:#?}", patches)));
-        self.apply_diff(&patches, style_mount);
+        self.apply_patches(&patches, backend)
+    }
+    /// The changes that turn this mounted tree into `new`, forcing the lazy
+    /// nodes of `new` that need it. They apply to this tree, or to a copy of
+    /// it mounted elsewhere, through `apply_patches`.
+    pub fn patches(&mut self, new: &mut Html<Msg>, parent_id: String) -> Vec<Patch<Msg>> {
+        self.prepare_lazy(new);
+        self.diff(new, parent_id)
+    }
+    /// Patches this tree and the document behind it, returning the messages
+    /// sent by lifecycle hooks.
+    pub fn apply_patches(&mut self, patches: &Vec<Patch<Msg>>, backend: &Backend) -> Vec<Msg> {
+        let mut messages: Vec<Msg> = Vec::new();
+        self.apply_diff(patches, backend, &mut messages);
+        messages
     }
     
     
@@ -427,13 +1513,129 @@ impl<Msg: Clone + Debug> Html<Msg> {
     pub fn id(&self) -> Option<String> {
         match &self {
             Html::Node{id, ..} => Some(id.clone()),
-            Html::Text{..} => None,
+            Html::Unmanaged{id, ..} => Some(id.clone()),
+            Html::Portal{id, ..} => Some(id.clone()),
+            _ => None,
         }
     }
-    fn events(&self) -> Option<&BTreeMap<String, Handler<Msg>>> {
+    pub fn is_unmanaged(&self) -> bool {
+        matches!(self, Html::Unmanaged{..})
+    }
+    /// Text of this subtree, like the DOM's `textContent`. Raw markup and
+    /// comments contribute nothing.
+    pub fn text_content(&self) -> String {
+        match &self {
+            Html::Text{value} => value.clone(),
+            _ => self
+                .children()
+                .iter()
+                .map(|child| child.text_content())
+                .collect::<Vec<String>>()
+                .join(""),
+        }
+    }
+    /// Tag name of an element, managed or not.
+    pub fn tag(&self) -> Option<&str> {
+        match &self {
+            Html::Node{tag, ..} | Html::Unmanaged{tag, ..} => Some(tag.as_str()),
+            _ => None,
+        }
+    }
+    /// Value of the markup attribute `key` as the DOM would report it: the
+    /// last one set wins, and toggles read as `""` when on. DOM properties
+    /// are not attributes.
+    pub fn attribute(&self, key: &str) -> Option<String> {
+        let mut result = None;
+        for attribute in self.attributes().iter().filter(|x| x.key() == key) {
+            result = match attribute {
+                Attribute::Pair{value, ..} => Some(value.clone()),
+                Attribute::Toggle{value: true, ..} => Some(String::new()),
+                Attribute::Toggle{value: false, ..} => None,
+                Attribute::Property{..} => result,
+            };
+        }
+        result
+    }
+    /// Names of the events this element has handlers for, sorted.
+    pub fn event_names(&self) -> Vec<&str> {
+        match self.events() {
+            Some(events) => events.keys().map(|x| x.as_str()).collect(),
+            None => Vec::new(),
+        }
+    }
+    pub fn children(&self) -> &[Html<Msg>] {
+        match &self {
+            Html::Node{children, ..} => children.as_slice(),
+            Html::Fragment{children} => children.as_slice(),
+            Html::Portal{children, ..} => children.as_slice(),
+            Html::Lazy{node, ..} => node.as_slice(),
+            _ => &[],
+        }
+    }
+    fn children_mut(&mut self) -> Option<&mut Vec<Html<Msg>>> {
+        match self {
+            Html::Node{children, ..} => Some(children),
+            Html::Fragment{children} => Some(children),
+            Html::Portal{children, ..} => Some(children),
+            Html::Lazy{node, ..} => Some(node),
+            _ => None,
+        }
+    }
+    fn flat_children_mut(&mut self) -> Vec<&mut Html<Msg>> {
+        let mut results: Vec<&mut Html<Msg>> = Vec::new();
+        if let Some(children) = self.children_mut() {
+            for child in children.iter_mut() {
+                match child {
+                    Html::Fragment{..} => results.append(&mut child.flat_children_mut()),
+                    _ => results.push(child),
+                }
+            }
+        }
+        results
+    }
+    /// Children with any nested fragments spliced in place.
+    fn flat_children(&self) -> Vec<&Html<Msg>> {
+        let mut results: Vec<&Html<Msg>> = Vec::new();
+        for child in self.children() {
+            match child {
+                Html::Fragment{..} => results.append(&mut child.flat_children()),
+                _ => results.push(child),
+            }
+        }
+        results
+    }
+    pub fn attributes(&self) -> &[Attribute] {
+        match &self {
+            Html::Node{attributes, ..} => attributes.as_slice(),
+            Html::Unmanaged{attributes, ..} => attributes.as_slice(),
+            _ => &[],
+        }
+    }
+    pub fn styling(&self) -> &[Style] {
+        match &self {
+            Html::Node{styling, ..} => styling.as_slice(),
+            Html::Unmanaged{styling, ..} => styling.as_slice(),
+            _ => &[],
+        }
+    }
+    pub fn node_ref(&self) -> Option<NodeRef> {
+        match &self {
+            Html::Node{node_ref, ..} => node_ref.clone(),
+            Html::Unmanaged{node_ref, ..} => node_ref.clone(),
+            _ => None,
+        }
+    }
+    pub fn lifecycle(&self) -> Option<Lifecycle<Msg>> {
+        match &self {
+            Html::Node{lifecycle, ..} => Some(lifecycle.clone()),
+            Html::Unmanaged{lifecycle, ..} => Some(lifecycle.clone()),
+            _ => None,
+        }
+    }
+    pub fn events(&self) -> Option<&BTreeMap<String, Handler<Msg>>> {
         match self {
             Html::Node{events, ..} => Some(events),
-            Html::Text{..} => None
+            _ => None
         }
     }
     fn get_mail(&self) -> Option<(String, JsValue)> {
@@ -441,7 +1643,7 @@ impl<Msg: Clone + Debug> Html<Msg> {
             Html::Node{mailbox: Mailbox(queue), ..} => {
                 queue.borrow_mut().pop_front()
             },
-            Html::Text{..} => None
+            _ => None
         }
     }
     fn get_mailbox(&self) -> Option<Rc<Mailbox>> {
@@ -449,7 +1651,7 @@ impl<Msg: Clone + Debug> Html<Msg> {
             Html::Node{mailbox, ..} => Some(
                 Rc::new(mailbox.clone())
             ),
-            Html::Text{..} => None
+            _ => None
         }
     }
     fn lookup_handler(&self, key: &String) -> Option<&Handler<Msg>> {
@@ -460,124 +1662,419 @@ impl<Msg: Clone + Debug> Html<Msg> {
                     None => None
                 }
             },
-            Html::Text{..} => None
+            _ => None
         }
     }
     
-    fn get_live(&self) -> Option<web_sys::Element> {
-        let window: web_sys::Window = web_sys::window()
-            .expect("window not available");
-        let document = window
-            .document()
-            .expect("document not available");
-        match self.id() {
-            Some(id) => {
-                document.get_element_by_id(id.as_str())
-            },
-            None => None
-        }
+    fn get_live(&self, backend: &Backend) -> Option<LiveNode> {
+        self.id().and_then(|id| backend.get_element_by_id(id.as_str()))
     }
     
     ///////////////////////////////////////////////////////////////////////////
     // CONSTRUCTION
     ///////////////////////////////////////////////////////////////////////////
     pub fn new_node(tag: String) -> Html<Msg> {
+        let namespace = Namespace::from_tag(tag.as_str());
+        Html::new_node_ns(tag, namespace)
+    }
+    pub fn new_svg_node(tag: String) -> Html<Msg> {
+        Html::new_node_ns(tag, Namespace::Svg)
+    }
+    pub fn new_node_ns(tag: String, namespace: Namespace) -> Html<Msg> {
         Html::Node {
             tag: tag,
-            id: format!("_{}", rand::random::<u16>()),
+            id: next_id(),
+            namespace,
             attributes: Vec::new(),
             styling: Vec::new(),
             events: BTreeMap::new(),
             mailbox: Mailbox::new(),
+            node_ref: None,
+            lifecycle: Lifecycle::new(),
+            children: Vec::new(),
+        }
+    }
+    pub fn new_fragment(children: Vec<Html<Msg>>) -> Html<Msg> {
+        Html::Fragment {
+            children,
+        }
+    }
+    pub fn new_comment(value: String) -> Html<Msg> {
+        Html::Comment {
+            value,
+        }
+    }
+    pub fn new_portal(target: String, children: Vec<Html<Msg>>) -> Html<Msg> {
+        let mut portal = Html::Portal {
+            target,
+            id: next_id(),
             children: Vec::new(),
+        };
+        for child in children {
+            portal.add_child(child);
+        }
+        portal
+    }
+    pub fn new_lazy<Args: Hash + 'static>(args: Args, view: fn(&Args)->Html<Msg>) -> Html<Msg>
+    where
+        Msg: 'static,
+    {
+        Html::Lazy {
+            hash: calculate_hash(&(&args, view as usize)),
+            view: LazyView {
+                fun: Rc::new(move || view(&args)),
+                namespace: None,
+            },
+            node: Vec::new(),
+        }
+    }
+    pub fn new_raw(value: String) -> Html<Msg> {
+        Html::Raw {
+            value,
+        }
+    }
+    pub fn new_sanitized_raw(value: &str, sanitizer: &Sanitizer) -> Html<Msg> {
+        Html::new_raw(sanitizer.sanitize(value))
+    }
+    /// Stand-in for a branch that renders nothing, so siblings keep their
+    /// positions when the branch toggles.
+    pub fn placeholder() -> Html<Msg> {
+        Html::new_comment(String::new())
+    }
+    /// The id is derived from `key`, so the same key keeps the same live
+    /// element for as long as the node stays under the same parent. Keys must
+    /// be unique in the document; moving a node to another parent in a single
+    /// update needs a new key, or both elements briefly share an id.
+    pub fn new_unmanaged(tag: String, key: String) -> Html<Msg> {
+        Html::Unmanaged {
+            namespace: Namespace::from_tag(tag.as_str()),
+            tag,
+            id: format!("_unmanaged_{}", key),
+            attributes: Vec::new(),
+            styling: Vec::new(),
+            node_ref: None,
+            lifecycle: Lifecycle::new(),
         }
     }
     
     ///////////////////////////////////////////////////////////////////////////
     // EXTERNAL - API
     ///////////////////////////////////////////////////////////////////////////
-    pub fn render(&self, style_mount: &StyleMount) -> String {
+    pub fn render(&self, backend: &Backend) -> String {
+        self.render_markup(Some(backend))
+    }
+    /// Markup for this node without inserting its styles into the document,
+    /// so it works off the browser, e.g. in native tests.
+    #[cfg(feature = "ssr")]
+    pub fn to_html_string(&self) -> String {
+        self.render_markup(None)
+    }
+    /// Deterministic, indented outline of this tree for snapshot tests and
+    /// code review: one node per line with its attributes, properties,
+    /// styles, event names, ref and hooks. Generated ids are left out. Lazy
+    /// nodes show their contents whether or not they were forced.
+    #[cfg(feature = "ssr")]
+    pub fn to_pretty_string(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        self.pretty_lines(0, &mut lines);
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+    fn pretty_lines(&self, depth: usize, lines: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        match &self {
+            Html::Node{children, ..} => {
+                lines.push(format!("{}<{}>", indent, self.pretty_start_tag()));
+                self.pretty_details(depth + 1, lines);
+                for child in children {
+                    child.pretty_lines(depth + 1, lines);
+                }
+            }
+            Html::Unmanaged{..} => {
+                lines.push(format!("{}unmanaged <{}>", indent, self.pretty_start_tag()));
+                self.pretty_details(depth + 1, lines);
+            }
+            Html::Fragment{children} => {
+                for child in children {
+                    child.pretty_lines(depth, lines);
+                }
+            }
+            Html::Comment{value} => lines.push(format!("{}<!--{}-->", indent, value)),
+            Html::Raw{value} => lines.push(format!("{}raw {:?}", indent, value)),
+            Html::Portal{target, children, ..} => {
+                lines.push(format!("{}portal {:?}", indent, target));
+                for child in children {
+                    child.pretty_lines(depth + 1, lines);
+                }
+            }
+            Html::Lazy{view, node, ..} => {
+                lines.push(format!("{}lazy", indent));
+                match node.first() {
+                    Some(node) => node.pretty_lines(depth + 1, lines),
+                    None => view.eval().pretty_lines(depth + 1, lines),
+                }
+            }
+            Html::Text{value} => lines.push(format!("{}{:?}", indent, value)),
+        }
+    }
+    /// Tag, prefixed by its namespace where the tag alone would imply
+    /// another, then the key of unmanaged nodes and the attributes.
+    fn pretty_start_tag(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if let Html::Node{tag, namespace, ..} | Html::Unmanaged{tag, namespace, ..} = &self {
+            let prefix = match namespace {
+                Namespace::Html => "html",
+                Namespace::Svg => "svg",
+            };
+            if *namespace == Namespace::from_tag(tag) {
+                parts.push(tag.clone());
+            } else {
+                parts.push(format!("{}:{}", prefix, tag));
+            }
+        }
+        if let Html::Unmanaged{id, ..} = &self {
+            parts.push(format!("key={:?}", id.trim_start_matches("_unmanaged_")));
+        }
+        for attribute in self.attributes() {
+            match attribute {
+                Attribute::Pair{key, value} => parts.push(format!("{}={:?}", key, value)),
+                Attribute::Toggle{key, value: true} => parts.push(key.clone()),
+                Attribute::Toggle{value: false, ..} => (),
+                Attribute::Property{key, value: PropertyValue::Bool(x)} => parts.push(format!(".{}={}", key, x)),
+                Attribute::Property{key, value: PropertyValue::String(x)} => parts.push(format!(".{}={:?}", key, x)),
+                Attribute::Property{key, value: PropertyValue::Number(x)} => parts.push(format!(".{}={}", key, x)),
+            }
+        }
+        parts.join(" ")
+    }
+    /// Styles, event names, ref and hooks of an element, one kind per line.
+    fn pretty_details(&self, depth: usize, lines: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        let decls = |styles: &[Style]| -> String {
+            styles
+                .iter()
+                .filter_map(|style| style.render_decl())
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        if !decls(self.styling()).is_empty() {
+            lines.push(format!("{}style {}", indent, decls(self.styling())));
+        }
+        for style in self.styling() {
+            if let Style::PseudoClass(name, body) = style {
+                lines.push(format!("{}style :{} {}", indent, name, decls(body)).trim_end().to_owned());
+            }
+        }
+        if let Some(events) = self.events().filter(|x| !x.is_empty()) {
+            let names: Vec<&str> = events.keys().map(|x| x.as_str()).collect();
+            lines.push(format!("{}on {}", indent, names.join(", ")));
+        }
+        if self.node_ref().is_some() {
+            lines.push(format!("{}ref", indent));
+        }
+        if let Some(lifecycle) = self.lifecycle() {
+            let mut hooks: Vec<&str> = Vec::new();
+            if lifecycle.on_mount.is_some() {hooks.push("mount")}
+            if lifecycle.on_update.is_some() {hooks.push("update")}
+            if lifecycle.on_unmount.is_some() {hooks.push("unmount")}
+            if !hooks.is_empty() {
+                lines.push(format!("{}hooks {}", indent, hooks.join(", ")));
+            }
+        }
+    }
+    fn render_markup(&self, backend: Option<&Backend>) -> String {
         match &self {
-            Html::Node{tag, id, attributes, children,..} => {
-                self.render_css(style_mount);
-                let attributes: Option<String> = self.render_attributes();
+            Html::Node{tag, children,..} => {
                 let children: String = children
                     .iter()
-                    .map(|c| c.render(style_mount))
+                    .map(|c| c.render_markup(backend))
                     .collect::<Vec<String>>()
                     .join("");
-                
-                if attributes.is_none() {
-                    format!(
-                        "<{tag} id={id}>{children}</{tag}>",
-                        id=id,
-                        tag=tag,
-                        children=children,
-                    )
-                } else {
-                    format!(
-                        "<{tag} id={id} {attributes}>{children}</{tag}>",
-                        id=id,
-                        tag=tag,
-                        attributes=attributes.unwrap(),
-                        children=children,
-                    )
-                }
-            }
-            Html::Text{value} => {value.clone()}
+                format!(
+                    "<{start}>{children}</{tag}>",
+                    start=self.render_start_tag(backend),
+                    tag=tag,
+                    children=children,
+                )
+            }
+            Html::Unmanaged{tag, ..} => {
+                format!(
+                    "<{start}></{tag}>",
+                    start=self.render_start_tag(backend),
+                    tag=tag,
+                )
+            }
+            Html::Fragment{children} => {
+                children
+                    .iter()
+                    .map(|c| c.render_markup(backend))
+                    .collect::<Vec<String>>()
+                    .join("")
+            }
+            Html::Comment{value} => {
+                format!("<!--{}-->", value.replace("--", "- -"))
+            }
+            Html::Raw{value} => {value.clone()}
+            Html::Portal{..} => {String::from("<!--portal-->")}
+            Html::Lazy{node, ..} => {
+                node.first().expect("lazy node rendered before being forced").render_markup(backend)
+            }
+            Html::Text{value} => {escape_markup(value)}
+        }
+    }
+    /// Builds a detached DOM tree for this node. Unlike `render`, elements
+    /// are created in their namespace so SVG works outside of markup parsing.
+    pub fn to_live(&self, backend: &Backend) -> LiveNode {
+        match &self {
+            Html::Node{tag, id, namespace, attributes, ..} | Html::Unmanaged{tag, id, namespace, attributes, ..} => {
+                self.render_css(backend);
+                let live = backend.create_element(tag.as_str(), namespace.uri());
+                backend.set_attribute(&live, "id", id.as_str(), None);
+                for attribute in attributes.iter().filter(|atr| atr.key() != "id") {
+                    attribute.set_live(backend, &live);
+                }
+                for child in self.children() {
+                    backend.append_child(&live, &child.to_live(backend));
+                }
+                live
+            }
+            Html::Fragment{children} => {
+                let live = backend.create_fragment();
+                for child in children {
+                    backend.append_child(&live, &child.to_live(backend));
+                }
+                live
+            }
+            Html::Comment{value} => {
+                backend.create_comment(value.as_str())
+            }
+            Html::Portal{..} => {
+                backend.create_comment("portal")
+            }
+            Html::Lazy{node, ..} => {
+                node.first().expect("lazy node mounted before being forced").to_live(backend)
+            }
+            Html::Raw{value} => {
+                backend.create_raw(value.as_str())
+            }
+            Html::Text{value} => {
+                backend.create_text(value.as_str())
+            }
         }
     }
     pub fn add_attribute(&mut self, attribute: Attribute) {
         match self {
-            Html::Node{ref mut attributes, ..} => {
+            Html::Node{ref mut attributes, ..} | Html::Unmanaged{ref mut attributes, ..} => {
                 attributes.push(attribute);
             }
-            Html::Text{..} => {panic!()}
+            _ => {panic!()}
         }
     }
     pub fn add_style(&mut self, style: Style) {
         match self {
-            Html::Node{ref mut styling, ..} => {
+            Html::Node{ref mut styling, ..} | Html::Unmanaged{ref mut styling, ..} => {
                 styling.push(style);
             }
-            Html::Text{..} => {panic!()}
+            _ => {panic!()}
         }
     }
     pub fn add_event_handler(&mut self, event_name: String, fun: Rc<Fn(JsValue)->Msg>) {
-        let handler: Handler<Msg> = {
-            use wasm_bindgen::JsCast;
-            let mailbox: Rc<Mailbox> = self.get_mailbox().expect("missing mailbox");
-            let closure: Closure<dyn FnMut(JsValue)> = Closure::wrap(Box::new(
-                {
-                    let mailbox = mailbox.clone();
-                    let event_name = event_name.to_owned();
-                    move |value: JsValue| {
-                        mailbox.insert(event_name.clone(), value);
-                    }
-                }
-            ));
-            let function: &js_sys::Function = closure.as_ref().unchecked_ref();
-            let function: js_sys::Function = function.clone();
-            closure.forget();
-            let handler =  Handler {
-                fun: fun,
-                js_ref: function
-            };
-            handler
-        };
+        self.add_handler(event_name, Handler::new(fun));
+    }
+    /// Sends `msg` on `event_name`; see `Handler::message`.
+    pub fn add_event_message(&mut self, event_name: String, msg: Msg)
+    where
+        Msg: 'static
+    {
+        self.add_handler(event_name, Handler::message(msg));
+    }
+    pub(crate) fn add_handler(&mut self, event_name: String, handler: Handler<Msg>) {
         match self {
             Html::Node{ref mut events, ..} => {
                 events.insert(event_name, handler);
             }
-            Html::Text{..} => {panic!()}
+            _ => {panic!()}
+        }
+    }
+    pub fn set_node_ref(&mut self, new_ref: NodeRef) {
+        match self {
+            Html::Node{ref mut node_ref, ..} | Html::Unmanaged{ref mut node_ref, ..} => {
+                *node_ref = Some(new_ref);
+            }
+            _ => {panic!()}
+        }
+    }
+    pub fn set_on_mount(&mut self, fun: HookFn<Msg>) {
+        match self {
+            Html::Node{ref mut lifecycle, ..} | Html::Unmanaged{ref mut lifecycle, ..} => {
+                lifecycle.on_mount = Some(Hook(fun));
+            }
+            _ => {panic!()}
+        }
+    }
+    pub fn set_on_update(&mut self, fun: HookFn<Msg>) {
+        match self {
+            Html::Node{ref mut lifecycle, ..} | Html::Unmanaged{ref mut lifecycle, ..} => {
+                lifecycle.on_update = Some(Hook(fun));
+            }
+            _ => {panic!()}
         }
     }
-    pub fn add_child(&mut self, child: Html<Msg>) {
+    pub fn set_on_unmount(&mut self, fun: HookFn<Msg>) {
         match self {
-            Html::Node{ref mut children, ..} => {
+            Html::Node{ref mut lifecycle, ..} | Html::Unmanaged{ref mut lifecycle, ..} => {
+                lifecycle.on_unmount = Some(Hook(fun));
+            }
+            _ => {panic!()}
+        }
+    }
+    pub fn add_child(&mut self, mut child: Html<Msg>) {
+        if let Html::Fragment{children: grandchildren} = child {
+            for grandchild in grandchildren {
+                self.add_child(grandchild);
+            }
+            return;
+        }
+        match self {
+            Html::Node{ref tag, ref namespace, ref mut children, ..} => {
+                child.set_namespace(namespace.for_children_of(tag.as_str()));
+                children.push(child);
+            }
+            Html::Portal{ref mut children, ..} => {
+                child.set_namespace(Namespace::Html);
                 children.push(child);
             }
-            Html::Text{..} => {panic!()}
+            _ => {panic!()}
+        }
+    }
+    /// Moves this subtree into the given namespace. Nested `svg` roots keep
+    /// their own namespace.
+    pub fn set_namespace(&mut self, new_namespace: Namespace) {
+        match self {
+            Html::Node{ref tag, ref mut namespace, ref mut children, ..} => {
+                if tag != "svg" {
+                    *namespace = new_namespace;
+                }
+                for child in children.iter_mut() {
+                    child.set_namespace(namespace.for_children_of(tag.as_str()));
+                }
+            }
+            Html::Unmanaged{ref tag, ref mut namespace, ..} => {
+                if tag != "svg" {
+                    *namespace = new_namespace;
+                }
+            }
+            Html::Fragment{ref mut children} => {
+                for child in children.iter_mut() {
+                    child.set_namespace(new_namespace.clone());
+                }
+            }
+            Html::Lazy{ref mut view, ref mut node, ..} => {
+                for child in node.iter_mut() {
+                    child.set_namespace(new_namespace.clone());
+                }
+                view.namespace = Some(new_namespace);
+            }
+            Html::Comment{..} | Html::Raw{..} | Html::Portal{..} | Html::Text{..} => ()
         }
     }
 }
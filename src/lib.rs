@@ -2,26 +2,31 @@
 
 #![allow(dead_code, unused)]
 
-use wasm_bindgen::prelude::*;
-use web_sys::console;
-use wasm_bindgen::JsValue;
-
 pub mod core;
 pub mod view_macro;
 pub mod css;
+#[cfg(feature = "dom")]
 pub mod cssom;
 pub mod html;
+pub mod query;
+pub mod template;
 pub mod sync;
+pub mod sanitize;
+pub mod platform;
+pub mod backend;
+pub mod mock_dom;
+pub mod debugger;
+pub mod persist;
+pub mod hot_reload;
+pub mod live;
+#[cfg(feature = "ssr")]
+pub mod testing;
 pub mod effect;
 
-#[wasm_bindgen]
-pub fn main() -> Result<(), JsValue> {
-    console_error_panic_hook::set_once();
-    // core::test();
-    effect::nav::test();
-    Ok(())
-}
-
-
-
+// PERSISTENT COLLECTIONS FOR MODELS - CHEAP TO CLONE & SNAPSHOT. MODELS MUST
+// BE `Hash`; `rpds::HashTrieMap` ISN'T, USE `rpds::RedBlackTreeMap` INSTEAD.
+pub use im;
+pub use rpds;
 
+// JSX-LIKE `html!`, CHECKED AT COMPILE TIME; SEE `view-prototype-macros`.
+pub use view_prototype_macros::html;
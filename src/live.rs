@@ -0,0 +1,322 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::rc::Rc;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+
+use crate::core::{Component, Process};
+use crate::html::{Html, PropertyValue};
+use crate::sync::Patch;
+use crate::platform::JsValue;
+use crate::backend::{Backend, HeadlessBackend, LiveNode};
+#[cfg(feature = "dom")]
+use crate::backend::WebBackend;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// TRANSPORT
+///////////////////////////////////////////////////////////////////////////////
+
+/// Carries encoded frames between the two ends, e.g. over a WebSocket.
+pub trait Transport {
+    fn send(&self, frame: Vec<u8>);
+    /// The next frame from the other end, if one has arrived. Never blocks.
+    fn receive(&self) -> Option<Vec<u8>>;
+}
+
+/// One end of an in-process transport; see `channel`.
+pub struct Channel {
+    sender: mpsc::Sender<Vec<u8>>,
+    receiver: mpsc::Receiver<Vec<u8>>,
+}
+
+/// Two connected ends, for running server and client in one process, e.g.
+/// in tests. Frames sent after the other end is dropped are discarded.
+pub fn channel() -> (Channel, Channel) {
+    let (to_b, from_a) = mpsc::channel();
+    let (to_a, from_b) = mpsc::channel();
+    let a = Channel {sender: to_b, receiver: from_b};
+    let b = Channel {sender: to_a, receiver: from_a};
+    (a, b)
+}
+
+impl Transport for Channel {
+    fn send(&self, frame: Vec<u8>) {
+        let _ = self.sender.send(frame);
+    }
+    fn receive(&self) -> Option<Vec<u8>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// FRAMES
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "Msg: Clone + Debug + Serialize",
+    deserialize = "Msg: Clone + Debug + Deserialize<'de> + 'static",
+))]
+pub enum ServerFrame<Msg> {
+    /// The whole view, sent first.
+    Mount(Html<Msg>),
+    Patch(Vec<Patch<Msg>>),
+}
+
+/// Clients only report events; the server resolves them against the
+/// handlers of the view it rendered, so a client can't send messages the
+/// view doesn't.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum ClientFrame {
+    /// `event_name` fired on the element `id`. `value` is what the handler
+    /// gets as its event: in the browser, the `value` of the event's
+    /// target, e.g. the text of an input; `None` for `undefined`.
+    Event {id: String, event_name: String, value: Option<PropertyValue>},
+    /// The client's document no longer matches its view, e.g. a script
+    /// removed an element; the server answers with `ServerFrame::Mount`.
+    Resync,
+}
+
+pub fn encode<T: Serialize>(frame: &T) -> Vec<u8> {
+    bincode::serialize(frame).expect("unable to encode frame")
+}
+
+/// `None` for frames that don't decode; those are dropped.
+pub fn decode<T: DeserializeOwned>(frame: &[u8]) -> Option<T> {
+    bincode::deserialize(frame).ok()
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// SERVER
+///////////////////////////////////////////////////////////////////////////////
+
+/// Runs the component natively, e.g. in a server process, and streams the
+/// patches of each render to a `LiveClient`, which sends events back.
+///
+/// The process runs on a `HeadlessBackend`: its view keeps the ids the
+/// client sees, and no document is kept. Handlers run here, with the value
+/// the client sent in place of the browser event. Build the server without
+/// `dom` for handlers to read it; outside wasm, a `dom` build can't make
+/// browser values and hands every handler `JsValue::UNDEFINED`.
+pub struct LiveServer<Model, Msg, T>
+where
+    Model: Debug + PartialEq + Clone + Hash,
+    Msg: Debug + PartialEq + Clone + Hash
+{
+    process: Process<Model, Msg>,
+    transport: Rc<T>,
+}
+
+impl<Model, Msg, T> LiveServer<Model, Msg, T>
+where
+    Model: Debug + PartialEq + Clone + Hash + 'static,
+    Msg: Debug + PartialEq + Clone + Hash + Serialize + 'static,
+    T: Transport + 'static
+{
+    /// Renders the view and sends it to the client.
+    pub fn new(spec: Component<Model, Msg>, transport: T) -> Self {
+        let process = Process::with_backend(spec, Rc::new(HeadlessBackend::new()));
+        let transport = Rc::new(transport);
+        transport.send(encode(&ServerFrame::Mount(process.view())));
+        process.on_patches({
+            let transport = transport.clone();
+            move |view, patches| {
+                let patches = client_patches(view, patches);
+                if !patches.is_empty() {
+                    transport.send(encode(&ServerFrame::Patch(patches)));
+                }
+            }
+        });
+        LiveServer {process, transport}
+    }
+    /// For middleware, the debugger and so on.
+    pub fn process(&self) -> &Process<Model, Msg> {
+        &self.process
+    }
+    /// Runs the handler of each event the client sent, in order, ticking
+    /// the process after each and sending the patches of any re-render.
+    /// Events on elements that are gone, or that have no handler for them,
+    /// are dropped.
+    pub fn tick(&self) {
+        while let Some(frame) = self.transport.receive() {
+            match decode(&frame) {
+                Some(ClientFrame::Event{id, event_name, value}) => {
+                    let value = to_js(value);
+                    if self.process.dispatch(&id, &event_name, value) {
+                        self.process.tick();
+                    }
+                }
+                Some(ClientFrame::Resync) => {
+                    self.transport.send(encode(&ServerFrame::Mount(self.process.view())));
+                }
+                None => (),
+            }
+        }
+        self.process.tick();
+    }
+}
+
+
+/// The patches that change what the client shows. Handlers, hooks and node
+/// refs only run on the server, so the client only needs to hear about
+/// events when the set it listens for changes.
+fn client_patches<Msg: Debug + Clone>(view: &Html<Msg>, patches: &[Patch<Msg>]) -> Vec<Patch<Msg>> {
+    let listening: HashMap<String, Vec<&String>> = view
+        .find_all(|node| node.events().is_some())
+        .into_iter()
+        .filter_map(|node| Some((node.id()?, node.events()?.keys().collect())))
+        .collect();
+    patches.iter().filter(|patch| match patch {
+        Patch::SetEvents{id, value} => {
+            listening.get(id) != Some(&value.keys().collect())
+        }
+        Patch::SetLifecycle{..} | Patch::SetNodeRef{..} => false,
+        _ => true,
+    }).cloned().collect()
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// CLIENT
+///////////////////////////////////////////////////////////////////////////////
+
+/// Mounts the view the server sends and applies its patches. Handlers never
+/// run here; their events go back to the server. Ids come from the server,
+/// so don't run a `Process` in the same document.
+pub struct LiveClient<Msg, T> {
+    transport: T,
+    backend: Rc<Backend>,
+    view_mount: LiveNode,
+    view: Option<Html<Msg>>,
+    /// Set once a resync is requested; patches are dropped until the
+    /// server's `Mount` arrives.
+    resyncing: bool,
+}
+
+impl<Msg, T> LiveClient<Msg, T>
+where
+    Msg: Debug + Clone + DeserializeOwned + 'static,
+    T: Transport
+{
+    #[cfg(feature = "dom")]
+    pub fn new(transport: T) -> Self {
+        LiveClient::with_backend(transport, Rc::new(WebBackend::new()))
+    }
+    pub fn with_backend(transport: T, backend: Rc<Backend>) -> Self {
+        let view_mount = backend.create_element("div", None);
+        backend.append_child(&backend.body(), &view_mount);
+        LiveClient {
+            transport,
+            backend,
+            view_mount,
+            view: None,
+            resyncing: false,
+        }
+    }
+    /// The view as last patched, once the server has sent it.
+    pub fn view(&self) -> Option<&Html<Msg>> {
+        self.view.as_ref()
+    }
+    /// Applies the frames the server sent, then sends back the events that
+    /// arrived since the last tick. Patches naming an element missing from
+    /// the document aren't applied; the client asks for the whole view
+    /// again instead.
+    pub fn tick(&mut self) {
+        while let Some(frame) = self.transport.receive() {
+            match decode(&frame) {
+                Some(ServerFrame::Mount(view)) => {
+                    self.resyncing = false;
+                    self.mount(view);
+                }
+                Some(ServerFrame::Patch(_)) if self.resyncing => (),
+                Some(ServerFrame::Patch(patches)) => {
+                    let backend = self.backend.as_ref();
+                    let missing = patches
+                        .iter()
+                        .filter_map(|patch| patch.id())
+                        .any(|id| backend.get_element_by_id(&id).is_none());
+                    if missing {
+                        self.resyncing = true;
+                        self.transport.send(encode(&ClientFrame::Resync));
+                    } else if let Some(view) = self.view.as_mut() {
+                        view.apply_patches(&patches, backend);
+                    }
+                }
+                None => (),
+            }
+        }
+        let mut frames: Vec<ClientFrame> = Vec::new();
+        if let Some(view) = &self.view {
+            take_events(view, &mut frames);
+        }
+        for frame in frames {
+            self.transport.send(encode(&frame));
+        }
+    }
+    fn mount(&mut self, view: Html<Msg>) {
+        let backend = self.backend.as_ref();
+        if let Some(old) = self.view.take() {
+            old.will_unmount(backend, &mut Vec::new());
+            backend.set_text_content(&self.view_mount, "");
+        }
+        let root_id = view.id().expect("missing id on root node");
+        backend.set_attribute(&self.view_mount, "id", root_id.as_str(), None);
+        for child in view.children() {
+            backend.append_child(&self.view_mount, &child.to_live(backend));
+        }
+        view.did_mount(backend, &mut Vec::new());
+        self.view = Some(view);
+    }
+}
+
+/// Empties the mailboxes in `node`, children first, into frames for the
+/// server.
+fn take_events<Msg: Debug + Clone>(node: &Html<Msg>, frames: &mut Vec<ClientFrame>) {
+    for child in node.children() {
+        take_events(child, frames);
+    }
+    if let Html::Node{id, mailbox, ..} = node {
+        while let Some((event_name, event)) = mailbox.remove() {
+            frames.push(ClientFrame::Event {
+                id: id.clone(),
+                event_name,
+                value: event_value(&event),
+            });
+        }
+    }
+}
+
+/// The part of a browser event a server handler can read.
+#[cfg(all(feature = "dom", target_arch = "wasm32"))]
+fn event_value(event: &JsValue) -> Option<PropertyValue> {
+    let target = js_sys::Reflect::get(event, &JsValue::from_str("target")).ok()?;
+    let value = js_sys::Reflect::get(&target, &JsValue::from_str("value")).ok()?;
+    PropertyValue::from_js(&value)
+}
+
+/// Without `dom`, events carry their value directly, e.g. from `MockDom`.
+#[cfg(not(feature = "dom"))]
+fn event_value(event: &JsValue) -> Option<PropertyValue> {
+    PropertyValue::from_js(event)
+}
+
+// `wasm_bindgen` VALUES CAN'T BE READ OR MADE OUTSIDE WASM
+#[cfg(all(feature = "dom", not(target_arch = "wasm32")))]
+fn event_value(_: &JsValue) -> Option<PropertyValue> {
+    None
+}
+
+#[cfg(any(not(feature = "dom"), target_arch = "wasm32"))]
+fn to_js(value: Option<PropertyValue>) -> JsValue {
+    value.map_or(JsValue::UNDEFINED, |x| x.to_js())
+}
+
+#[cfg(all(feature = "dom", not(target_arch = "wasm32")))]
+fn to_js(_: Option<PropertyValue>) -> JsValue {
+    JsValue::UNDEFINED
+}
@@ -0,0 +1,358 @@
+use std::fmt::Debug;
+use std::collections::{BTreeMap, HashMap};
+use std::cell::RefCell;
+use crate::platform::JsValue;
+
+use crate::backend::{Backend, LiveNode};
+use crate::html::{Html, Mailbox, PropertyValue, Style};
+
+
+///////////////////////////////////////////////////////////////////////////////
+// NODES & OPERATIONS
+///////////////////////////////////////////////////////////////////////////////
+
+/// Index of a node in a `MockDom`. The body is always node `0`.
+pub type MockId = usize;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum MockKind {
+    Element {
+        tag: String,
+        namespace: Option<String>,
+    },
+    Text(String),
+    Comment(String),
+    Fragment,
+    /// Unparsed markup from `Html::Raw`.
+    Raw(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MockNode {
+    pub kind: MockKind,
+    pub attributes: BTreeMap<String, String>,
+    pub properties: BTreeMap<String, PropertyValue>,
+    pub parent: Option<MockId>,
+    pub children: Vec<MockId>,
+}
+
+/// One call made on the backend, in order.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Op {
+    CreateElement {node: MockId, tag: String, namespace: Option<String>},
+    CreateText {node: MockId, value: String},
+    CreateComment {node: MockId, value: String},
+    CreateFragment {node: MockId},
+    CreateRaw {node: MockId, markup: String},
+    SetAttribute {node: MockId, key: String, value: String},
+    RemoveAttribute {node: MockId, key: String},
+    SetProperty {node: MockId, key: String, value: PropertyValue},
+    AppendChild {parent: MockId, child: MockId},
+    ReplaceNode {old: MockId, new: MockId},
+    RemoveNode {node: MockId},
+    SetTextContent {node: MockId, value: String},
+    AddListener {node: MockId, event_name: String},
+    RemoveListener {node: MockId, event_name: String},
+    InsertRule(String),
+    DeleteRules(String),
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// MOCK DOM
+///////////////////////////////////////////////////////////////////////////////
+
+/// In-memory `Backend` that records every operation. Lookups by id only see
+/// nodes attached to the body, as in a real document. Lifecycle hooks and
+/// node refs need browser elements, so they do not run against it.
+#[derive(Debug)]
+pub struct MockDom {
+    nodes: RefCell<Vec<MockNode>>,
+    ops: RefCell<Vec<Op>>,
+    rules: RefCell<Vec<String>>,
+    listeners: RefCell<HashMap<(MockId, String), Mailbox>>,
+}
+
+impl MockDom {
+    pub fn new() -> Self {
+        let body = MockNode {
+            kind: MockKind::Element {tag: String::from("body"), namespace: None},
+            attributes: BTreeMap::new(),
+            properties: BTreeMap::new(),
+            parent: None,
+            children: Vec::new(),
+        };
+        MockDom {
+            nodes: RefCell::new(vec![body]),
+            ops: RefCell::new(Vec::new()),
+            rules: RefCell::new(Vec::new()),
+            listeners: RefCell::new(HashMap::new()),
+        }
+    }
+    pub fn ops(&self) -> Vec<Op> {
+        self.ops.borrow().clone()
+    }
+    /// Returns the operations recorded so far and starts a new log.
+    pub fn take_ops(&self) -> Vec<Op> {
+        self.ops.borrow_mut().drain(..).collect()
+    }
+    pub fn rules(&self) -> Vec<String> {
+        self.rules.borrow().clone()
+    }
+    pub fn node(&self, node: MockId) -> MockNode {
+        self.nodes.borrow()[node].clone()
+    }
+    pub fn find_by_id(&self, id: &str) -> Option<MockId> {
+        self.find_attached(0, &|node| node.attributes.get("id").map(|x| x.as_str()) == Some(id))
+    }
+    /// First attached element with this tag, in document order.
+    pub fn find_by_tag(&self, tag: &str) -> Option<MockId> {
+        self.find_attached(0, &|node| match &node.kind {
+            MockKind::Element{tag: node_tag, ..} => node_tag == tag,
+            _ => false,
+        })
+    }
+    /// Markup of the body's contents.
+    pub fn body_html(&self) -> String {
+        self.node(0).children.iter().map(|child| self.html(*child)).collect()
+    }
+    /// `body_html` without the `_`-prefixed ids vnodes give their elements,
+    /// which depend on how many nodes were built before.
+    pub fn bare_body_html(&self) -> String {
+        strip_vnode_ids(self.body_html())
+    }
+    /// `html` without vnode ids; see `bare_body_html`.
+    pub fn bare_html(&self, node: MockId) -> String {
+        strip_vnode_ids(self.html(node))
+    }
+    /// Appends `view` to the body the way `Process` mounts its view, and
+    /// returns the messages its mount produced.
+    pub fn mount<Msg: Clone + Debug>(&self, view: &Html<Msg>) -> Vec<Msg> {
+        let mut messages = Vec::new();
+        self.append_child(&self.body(), &view.to_live(self));
+        view.did_mount(self, &mut messages);
+        messages
+    }
+    /// Markup of `node`, attributes in key order.
+    pub fn html(&self, node: MockId) -> String {
+        let node = self.node(node);
+        let children: String = node.children.iter().map(|child| self.html(*child)).collect();
+        match node.kind {
+            MockKind::Element{tag, ..} => {
+                let attributes: String = node.attributes
+                    .iter()
+                    .map(|(key, value)| format!(" {}=\"{}\"", key, value))
+                    .collect();
+                format!("<{tag}{attributes}>{children}</{tag}>", tag=tag, attributes=attributes, children=children)
+            }
+            MockKind::Text(value) => value,
+            MockKind::Comment(value) => format!("<!--{}-->", value),
+            MockKind::Fragment => children,
+            MockKind::Raw(markup) => markup,
+        }
+    }
+    /// Names of the events `node` has listeners for, sorted.
+    pub fn listeners(&self, node: MockId) -> Vec<String> {
+        let mut names: Vec<String> = self.listeners
+            .borrow()
+            .keys()
+            .filter(|(x, _)| *x == node)
+            .map(|(_, name)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+    /// Fires `event_name` on `node` as a browser would; the owning `Process`
+    /// picks it up on its next tick. Returns whether anything listened.
+    pub fn dispatch(&self, node: MockId, event_name: &str, value: JsValue) -> bool {
+        let mailbox = self.listeners.borrow().get(&(node, event_name.to_owned())).cloned();
+        match mailbox {
+            Some(mailbox) => {
+                mailbox.insert(event_name.to_owned(), value);
+                true
+            }
+            None => false,
+        }
+    }
+    fn find_attached(&self, root: MockId, predicate: &Fn(&MockNode)->bool) -> Option<MockId> {
+        let children = {
+            let nodes = self.nodes.borrow();
+            if predicate(&nodes[root]) {
+                return Some(root);
+            }
+            nodes[root].children.clone()
+        };
+        children.into_iter().filter_map(|child| self.find_attached(child, predicate)).next()
+    }
+    fn id(&self, node: &LiveNode) -> MockId {
+        *node.downcast_ref::<MockId>().expect("not a mock node")
+    }
+    fn push(&self, kind: MockKind) -> MockId {
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.push(MockNode {
+            kind,
+            attributes: BTreeMap::new(),
+            properties: BTreeMap::new(),
+            parent: None,
+            children: Vec::new(),
+        });
+        nodes.len() - 1
+    }
+    fn record(&self, op: Op) {
+        self.ops.borrow_mut().push(op);
+    }
+    fn detach(&self, node: MockId) {
+        let mut nodes = self.nodes.borrow_mut();
+        if let Some(parent) = nodes[node].parent.take() {
+            nodes[parent].children.retain(|x| *x != node);
+        }
+    }
+    /// The nodes inserted in place of `node`: a fragment's children, which
+    /// leave it empty, or the node itself.
+    fn take_inserted(&self, node: MockId) -> Vec<MockId> {
+        let is_fragment = self.nodes.borrow()[node].kind == MockKind::Fragment;
+        if is_fragment {
+            let mut nodes = self.nodes.borrow_mut();
+            let children: Vec<MockId> = nodes[node].children.drain(..).collect();
+            for child in children.iter() {
+                nodes[*child].parent = None;
+            }
+            children
+        } else {
+            self.detach(node);
+            vec![node]
+        }
+    }
+}
+
+impl Default for MockDom {
+    fn default() -> Self {
+        MockDom::new()
+    }
+}
+
+fn strip_vnode_ids(mut html: String) -> String {
+    while let Some(start) = html.find(" id=\"_") {
+        let end = html[start + 6..].find('"').map_or(html.len(), |x| start + 7 + x);
+        html.replace_range(start..end, "");
+    }
+    html
+}
+
+impl Backend for MockDom {
+    fn create_element(&self, tag: &str, namespace: Option<&str>) -> LiveNode {
+        let namespace = namespace.map(|x| x.to_owned());
+        let node = self.push(MockKind::Element {tag: tag.to_owned(), namespace: namespace.clone()});
+        self.record(Op::CreateElement {node, tag: tag.to_owned(), namespace});
+        LiveNode::new(node)
+    }
+    fn create_text(&self, value: &str) -> LiveNode {
+        let node = self.push(MockKind::Text(value.to_owned()));
+        self.record(Op::CreateText {node, value: value.to_owned()});
+        LiveNode::new(node)
+    }
+    fn create_comment(&self, value: &str) -> LiveNode {
+        let node = self.push(MockKind::Comment(value.to_owned()));
+        self.record(Op::CreateComment {node, value: value.to_owned()});
+        LiveNode::new(node)
+    }
+    fn create_fragment(&self) -> LiveNode {
+        let node = self.push(MockKind::Fragment);
+        self.record(Op::CreateFragment {node});
+        LiveNode::new(node)
+    }
+    fn create_raw(&self, markup: &str) -> LiveNode {
+        let fragment = self.push(MockKind::Fragment);
+        let raw = self.push(MockKind::Raw(markup.to_owned()));
+        {
+            let mut nodes = self.nodes.borrow_mut();
+            nodes[fragment].children.push(raw);
+            nodes[raw].parent = Some(fragment);
+        }
+        self.record(Op::CreateRaw {node: fragment, markup: markup.to_owned()});
+        LiveNode::new(fragment)
+    }
+    fn get_element_by_id(&self, id: &str) -> Option<LiveNode> {
+        self.find_by_id(id).map(LiveNode::new)
+    }
+    fn body(&self) -> LiveNode {
+        LiveNode::new(0 as MockId)
+    }
+    fn set_attribute(&self, node: &LiveNode, key: &str, value: &str, namespace: Option<&str>) {
+        let node = self.id(node);
+        self.nodes.borrow_mut()[node].attributes.insert(key.to_owned(), value.to_owned());
+        self.record(Op::SetAttribute {node, key: key.to_owned(), value: value.to_owned()});
+    }
+    fn remove_attribute(&self, node: &LiveNode, key: &str, namespace: Option<&str>) {
+        let node = self.id(node);
+        self.nodes.borrow_mut()[node].attributes.remove(key);
+        self.record(Op::RemoveAttribute {node, key: key.to_owned()});
+    }
+    fn set_property(&self, node: &LiveNode, key: &str, value: &PropertyValue) {
+        let node = self.id(node);
+        if self.nodes.borrow()[node].properties.get(key) == Some(value) {
+            return;
+        }
+        self.nodes.borrow_mut()[node].properties.insert(key.to_owned(), value.clone());
+        self.record(Op::SetProperty {node, key: key.to_owned(), value: value.clone()});
+    }
+    fn append_child(&self, parent: &LiveNode, child: &LiveNode) {
+        let (parent, child) = (self.id(parent), self.id(child));
+        self.record(Op::AppendChild {parent, child});
+        for node in self.take_inserted(child) {
+            let mut nodes = self.nodes.borrow_mut();
+            nodes[node].parent = Some(parent);
+            nodes[parent].children.push(node);
+        }
+    }
+    fn replace_node(&self, old: &LiveNode, new: &LiveNode) {
+        let (old, new) = (self.id(old), self.id(new));
+        self.record(Op::ReplaceNode {old, new});
+        let parent = self.nodes.borrow()[old].parent.expect("missing parent node");
+        let inserted = self.take_inserted(new);
+        let mut nodes = self.nodes.borrow_mut();
+        let ix = nodes[parent].children.iter().position(|x| *x == old).expect("missing child");
+        nodes[parent].children.splice(ix..ix + 1, inserted.iter().cloned());
+        nodes[old].parent = None;
+        for node in inserted {
+            nodes[node].parent = Some(parent);
+        }
+    }
+    fn remove_node(&self, node: &LiveNode) {
+        let node = self.id(node);
+        self.record(Op::RemoveNode {node});
+        self.detach(node);
+    }
+    fn set_text_content(&self, node: &LiveNode, value: &str) {
+        let node = self.id(node);
+        self.record(Op::SetTextContent {node, value: value.to_owned()});
+        let children: Vec<MockId> = self.nodes.borrow_mut()[node].children.drain(..).collect();
+        for child in children {
+            self.nodes.borrow_mut()[child].parent = None;
+        }
+        if !value.is_empty() {
+            let text = self.push(MockKind::Text(value.to_owned()));
+            let mut nodes = self.nodes.borrow_mut();
+            nodes[text].parent = Some(node);
+            nodes[node].children.push(text);
+        }
+    }
+    fn add_listener(&self, node: &LiveNode, id: &str, event_name: &str, mailbox: &Mailbox) {
+        let node = self.id(node);
+        self.listeners.borrow_mut().insert((node, event_name.to_owned()), mailbox.clone());
+        self.record(Op::AddListener {node, event_name: event_name.to_owned()});
+    }
+    fn remove_listener(&self, node: &LiveNode, id: &str, event_name: &str) {
+        let node = self.id(node);
+        self.listeners.borrow_mut().remove(&(node, event_name.to_owned()));
+        self.record(Op::RemoveListener {node, event_name: event_name.to_owned()});
+    }
+    fn insert_rule(&self, rule: &str) {
+        self.rules.borrow_mut().push(rule.to_owned());
+        self.record(Op::InsertRule(rule.to_owned()));
+    }
+    fn delete_rules(&self, node_id: &str) {
+        self.rules.borrow_mut().retain(|rule| !Style::is_rule_for(rule, node_id));
+        self.record(Op::DeleteRules(node_id.to_owned()));
+    }
+}
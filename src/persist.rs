@@ -0,0 +1,241 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use serde::{self, Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// STORAGE
+///////////////////////////////////////////////////////////////////////////////
+
+/// String key-value store the model is persisted to.
+pub trait Storage {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&self, key: &str, value: &str);
+    fn remove(&self, key: &str);
+}
+
+/// `window.localStorage`. Failures (private mode, quota) are ignored, the
+/// app simply starts from its initial model next time.
+#[cfg(feature = "dom")]
+#[derive(Debug, Clone, Default)]
+pub struct LocalStorage;
+
+/// `window.sessionStorage`; survives reloads but not closing the tab.
+#[cfg(feature = "dom")]
+#[derive(Debug, Clone, Default)]
+pub struct SessionStorage;
+
+#[cfg(feature = "dom")]
+impl Storage for LocalStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        get_item(local_storage(), key)
+    }
+    fn set(&self, key: &str, value: &str) {
+        set_item(local_storage(), key, value)
+    }
+    fn remove(&self, key: &str) {
+        remove_item(local_storage(), key)
+    }
+}
+
+#[cfg(feature = "dom")]
+impl Storage for SessionStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        get_item(session_storage(), key)
+    }
+    fn set(&self, key: &str, value: &str) {
+        set_item(session_storage(), key, value)
+    }
+    fn remove(&self, key: &str) {
+        remove_item(session_storage(), key)
+    }
+}
+
+#[cfg(feature = "dom")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(feature = "dom")]
+fn session_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.session_storage().ok()?
+}
+
+#[cfg(feature = "dom")]
+fn get_item(storage: Option<web_sys::Storage>, key: &str) -> Option<String> {
+    storage?.get_item(key).ok()?
+}
+
+#[cfg(feature = "dom")]
+fn set_item(storage: Option<web_sys::Storage>, key: &str, value: &str) {
+    if let Some(storage) = storage {
+        storage.set_item(key, value);
+    }
+}
+
+#[cfg(feature = "dom")]
+fn remove_item(storage: Option<web_sys::Storage>, key: &str) {
+    if let Some(storage) = storage {
+        storage.remove_item(key);
+    }
+}
+
+/// In-memory store for tests. Clones share their contents.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage(Rc<RefCell<HashMap<String, String>>>);
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        MemoryStorage::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.borrow().get(key).cloned()
+    }
+    fn set(&self, key: &str, value: &str) {
+        self.0.borrow_mut().insert(key.to_owned(), value.to_owned());
+    }
+    fn remove(&self, key: &str) {
+        self.0.borrow_mut().remove(key);
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// PERSISTENCE
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Stored {
+    version: u32,
+    model: Value,
+}
+
+/// Upgrades a model saved under an older version; see `Persistence::migrate`.
+type Migration = Rc<Fn(u32, Value)->Option<Value>>;
+type Encode<Model> = Rc<Fn(&Model)->Option<Value>>;
+
+/// Saves a model under `{namespace}/model`, tagged with a schema version.
+/// A model saved under an older version is passed through the migration
+/// hook on load; without one, or when it returns `None`, it is discarded.
+#[derive(Clone)]
+pub struct Persistence<Model> {
+    storage: Rc<Storage>,
+    key: String,
+    version: u32,
+    debounce_ms: f64,
+    migrate: Option<Migration>,
+    encode: Encode<Model>,
+    decode: Rc<Fn(Value)->Option<Model>>,
+    /// Time of the latest change not yet saved.
+    pending: Rc<Cell<Option<f64>>>,
+}
+
+impl<Model> Debug for Persistence<Model> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> fmt::Result {
+        write!(f, "Persistence({:?}, v{})", self.key, self.version)
+    }
+}
+
+impl<Model> Persistence<Model> {
+    pub fn new<S>(storage: S, namespace: &str) -> Self
+    where
+        S: Storage + 'static,
+        Model: Serialize + DeserializeOwned + 'static,
+    {
+        Persistence {
+            storage: Rc::new(storage),
+            key: format!("{}/model", namespace),
+            version: 0,
+            debounce_ms: 500.0,
+            migrate: None,
+            encode: Rc::new(|model| serde_json::to_value(model).ok()),
+            decode: Rc::new(|value| serde_json::from_value(value).ok()),
+            pending: Rc::new(Cell::new(None)),
+        }
+    }
+    #[cfg(feature = "dom")]
+    pub fn local(namespace: &str) -> Self
+    where
+        Model: Serialize + DeserializeOwned + 'static,
+    {
+        Persistence::new(LocalStorage, namespace)
+    }
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+    /// Upgrades a model saved under an older version, given that version
+    /// and the saved JSON.
+    pub fn migrate<F>(mut self, migrate: F) -> Self
+    where
+        F: Fn(u32, Value)->Option<Value> + 'static
+    {
+        self.migrate = Some(Rc::new(migrate));
+        self
+    }
+    /// How long the model must stay unchanged before it is saved, in
+    /// milliseconds.
+    pub fn debounce(mut self, ms: f64) -> Self {
+        self.debounce_ms = ms;
+        self
+    }
+    pub fn key(&self) -> &str {
+        self.key.as_str()
+    }
+    pub fn load(&self) -> Option<Model> {
+        let stored: Stored = serde_json::from_str(&self.storage.get(&self.key)?).ok()?;
+        let model = if stored.version == self.version {
+            stored.model
+        } else {
+            self.migrate.as_ref()?.as_ref()(stored.version, stored.model)?
+        };
+        self.decode.as_ref()(model)
+    }
+    pub fn save(&self, model: &Model) {
+        self.pending.set(None);
+        let stored = self.encode.as_ref()(model).map(|model| Stored {
+            version: self.version,
+            model,
+        });
+        if let Some(value) = stored.and_then(|x| serde_json::to_string(&x).ok()) {
+            self.storage.set(&self.key, &value);
+        }
+    }
+    pub fn clear(&self) {
+        self.pending.set(None);
+        self.storage.remove(&self.key);
+    }
+    /// Notes a model change at `now` (milliseconds).
+    pub fn changed(&self, now: f64) {
+        self.pending.set(Some(now));
+    }
+    /// Saves `model` once no change has been made for the debounce period.
+    pub fn flush(&self, now: f64, model: &Model) -> bool {
+        match self.pending.get() {
+            Some(since) if now - since >= self.debounce_ms => {
+                self.save(model);
+                true
+            }
+            _ => false,
+        }
+    }
+    /// Saves `model` now if a change is still waiting out the debounce
+    /// period, e.g. before the page goes away.
+    pub fn flush_pending(&self, model: &Model) -> bool {
+        match self.pending.get() {
+            Some(_) => {
+                self.save(model);
+                true
+            }
+            None => false,
+        }
+    }
+}
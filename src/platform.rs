@@ -0,0 +1,123 @@
+///////////////////////////////////////////////////////////////////////////////
+// BROWSER TYPES
+///////////////////////////////////////////////////////////////////////////////
+
+// The vdom is written against these. With the `dom` feature they are the real
+// `wasm_bindgen`/`web_sys` types; without it, stand-ins so the crate builds for
+// servers and CLIs. The stand-in `JsValue` only holds primitives, e.g. the
+// value of an input, with the same constructors and accessors as the real one;
+// no `Element` exists, so node refs stay empty and lifecycle hooks never run.
+
+#[cfg(feature = "dom")]
+pub use wasm_bindgen::JsValue;
+
+#[cfg(feature = "dom")]
+pub use web_sys::Element;
+
+#[cfg(not(feature = "dom"))]
+#[derive(Debug, PartialEq, Clone)]
+pub struct JsValue(Primitive);
+
+#[cfg(not(feature = "dom"))]
+#[derive(Debug, PartialEq, Clone)]
+enum Primitive {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+#[cfg(not(feature = "dom"))]
+impl JsValue {
+    pub const UNDEFINED: JsValue = JsValue(Primitive::Undefined);
+    pub const NULL: JsValue = JsValue(Primitive::Null);
+    // NAMED AFTER `wasm_bindgen::JsValue::from_str`, WHICH CAN'T FAIL
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(value: &str) -> JsValue {
+        JsValue(Primitive::String(value.to_owned()))
+    }
+    pub fn from_f64(value: f64) -> JsValue {
+        JsValue(Primitive::Number(value))
+    }
+    pub fn from_bool(value: bool) -> JsValue {
+        JsValue(Primitive::Bool(value))
+    }
+    pub fn is_undefined(&self) -> bool {
+        self.0 == Primitive::Undefined
+    }
+    pub fn is_null(&self) -> bool {
+        self.0 == Primitive::Null
+    }
+    pub fn as_string(&self) -> Option<String> {
+        match &self.0 {
+            Primitive::String(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.0 {
+            Primitive::Number(value) => Some(value),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.0 {
+            Primitive::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(feature = "dom"))]
+impl<'a> From<&'a str> for JsValue {
+    fn from(value: &'a str) -> JsValue {
+        JsValue::from_str(value)
+    }
+}
+
+#[cfg(not(feature = "dom"))]
+impl From<String> for JsValue {
+    fn from(value: String) -> JsValue {
+        JsValue(Primitive::String(value))
+    }
+}
+
+#[cfg(not(feature = "dom"))]
+impl From<f64> for JsValue {
+    fn from(value: f64) -> JsValue {
+        JsValue::from_f64(value)
+    }
+}
+
+#[cfg(not(feature = "dom"))]
+impl From<bool> for JsValue {
+    fn from(value: bool) -> JsValue {
+        JsValue::from_bool(value)
+    }
+}
+
+#[cfg(not(feature = "dom"))]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Element(());
+
+
+///////////////////////////////////////////////////////////////////////////////
+// CLOCK
+///////////////////////////////////////////////////////////////////////////////
+
+/// Milliseconds since the Unix epoch.
+#[cfg(feature = "dom")]
+pub fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Milliseconds since the Unix epoch.
+#[cfg(not(feature = "dom"))]
+pub fn now() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs() as f64 * 1000.0 + x.subsec_millis() as f64)
+        .unwrap_or(0.0)
+}
@@ -0,0 +1,406 @@
+use std::fmt;
+use std::fmt::Debug;
+
+use crate::html::Html;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// SELECTORS
+///////////////////////////////////////////////////////////////////////////////
+
+/// A parsed CSS-like selector. Supported:
+/// - `li`, `*` - tag names;
+/// - `.done` - a word of the `class` attribute;
+/// - `[name]`, `[name=email]`, `[name="email"]`, and `^=`, `$=`, `*=`, `~=`;
+/// - `:text("Save")` - text content, trimmed, is exactly this;
+/// - `:contains("Save")` - text content includes this;
+/// - `ul li` (descendant), `ul > li` (child), and `a, b` (either).
+///
+/// Ids are generated, so `#id` is not supported.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Selector {
+    source: String,
+    alternatives: Vec<Complex>,
+}
+
+/// Compounds from the outermost ancestor to the subject, each with the
+/// combinator relating it to the previous one.
+#[derive(Debug, PartialEq, Clone)]
+struct Complex(Vec<(Combinator, Compound)>);
+
+#[derive(Debug, PartialEq, Clone)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+struct Compound {
+    tag: Option<String>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Filter {
+    Class(String),
+    Attribute(String, Option<(AttributeOp, String)>),
+    Text(String),
+    Contains(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum AttributeOp {
+    Equals,
+    Prefix,
+    Suffix,
+    Substring,
+    Word,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SelectorError {
+    pub selector: String,
+    /// Byte offset of the problem.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid selector {:?} at {}: {}", self.selector, self.position, self.message)
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        Parser {source: selector, position: 0}.selector()
+    }
+    /// Whether `node` matches, given its element ancestors, nearest last.
+    pub fn matches<Msg: Clone + Debug>(&self, node: &Html<Msg>, ancestors: &[&Html<Msg>]) -> bool {
+        self.alternatives.iter().any(|complex| complex.matches(node, ancestors))
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source.trim())
+    }
+}
+
+impl Complex {
+    fn matches<Msg: Clone + Debug>(&self, node: &Html<Msg>, ancestors: &[&Html<Msg>]) -> bool {
+        let (subject, rest) = self.0.split_last().expect("empty complex selector");
+        subject.1.matches(node) && Complex::matches_ancestors(rest, &subject.0, ancestors)
+    }
+    /// Whether `compounds` (outermost first) match within `ancestors`, the
+    /// last of which stands in `combinator` relation to what was matched.
+    fn matches_ancestors<Msg: Clone + Debug>(
+        compounds: &[(Combinator, Compound)],
+        combinator: &Combinator,
+        ancestors: &[&Html<Msg>],
+    ) -> bool {
+        let (compound, rest) = match compounds.split_last() {
+            None => return true,
+            Some(x) => x,
+        };
+        match combinator {
+            Combinator::Child => match ancestors.split_last() {
+                Some((parent, above)) => {
+                    compound.1.matches(parent) && Complex::matches_ancestors(rest, &compound.0, above)
+                }
+                None => false,
+            },
+            Combinator::Descendant => (0..ancestors.len()).rev().any(|ix| {
+                compound.1.matches(ancestors[ix])
+                    && Complex::matches_ancestors(rest, &compound.0, &ancestors[..ix])
+            }),
+        }
+    }
+}
+
+impl Compound {
+    fn matches<Msg: Clone + Debug>(&self, node: &Html<Msg>) -> bool {
+        let tag = match node.tag() {
+            Some(tag) => tag,
+            None => return false,
+        };
+        if self.tag.as_ref().map(|x| x != tag).unwrap_or(false) {
+            return false;
+        }
+        self.filters.iter().all(|filter| match filter {
+            Filter::Class(name) => node
+                .attribute("class")
+                .map(|x| x.split_whitespace().any(|word| word == name))
+                .unwrap_or(false),
+            Filter::Attribute(key, None) => node.attribute(key).is_some(),
+            Filter::Attribute(key, Some((op, value))) => match node.attribute(key) {
+                None => false,
+                Some(actual) => match op {
+                    AttributeOp::Equals => &actual == value,
+                    AttributeOp::Prefix => actual.starts_with(value.as_str()),
+                    AttributeOp::Suffix => actual.ends_with(value.as_str()),
+                    AttributeOp::Substring => actual.contains(value.as_str()),
+                    AttributeOp::Word => actual.split_whitespace().any(|word| word == value),
+                },
+            },
+            Filter::Text(text) => node.text_content().trim() == text,
+            Filter::Contains(text) => node.text_content().contains(text.as_str()),
+        })
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// PARSER
+///////////////////////////////////////////////////////////////////////////////
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: &str) -> Result<T, SelectorError> {
+        Err(SelectorError {
+            selector: self.source.to_owned(),
+            position: self.position,
+            message: message.to_owned(),
+        })
+    }
+    fn peek(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.position;
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.position += c.len_utf8();
+        }
+        self.position > start
+    }
+    /// Tag, class or attribute name. Attribute names may be prefixed, as in
+    /// `xlink:href`.
+    fn ident(&mut self, allow_colon: bool) -> Result<String, SelectorError> {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '-' || c == '_' || (allow_colon && c == ':') {
+                self.position += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+        if self.position == start {
+            return self.error("expected a name");
+        }
+        Ok(self.source[start..self.position].to_owned())
+    }
+    /// A quoted string, or a bare name.
+    fn value(&mut self) -> Result<String, SelectorError> {
+        let quote = match self.peek() {
+            Some(c @ '"') | Some(c @ '\'') => c,
+            _ => return self.ident(false),
+        };
+        self.position += 1;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                None => return self.error("unterminated string"),
+                Some(c) if c == quote => {
+                    self.position += 1;
+                    return Ok(value);
+                }
+                Some('\\') => {
+                    self.position += 1;
+                    match self.peek() {
+                        Some(c) => {
+                            value.push(c);
+                            self.position += c.len_utf8();
+                        }
+                        None => return self.error("unterminated string"),
+                    }
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.position += c.len_utf8();
+                }
+            }
+        }
+    }
+    fn selector(mut self) -> Result<Selector, SelectorError> {
+        let mut complexes = vec![self.complex()?];
+        while self.eat(',') {
+            complexes.push(self.complex()?);
+        }
+        if self.position < self.source.len() {
+            return self.error("unexpected character");
+        }
+        Ok(Selector {source: self.source.to_owned(), alternatives: complexes})
+    }
+    fn complex(&mut self) -> Result<Complex, SelectorError> {
+        self.skip_whitespace();
+        let mut compounds = vec![(Combinator::Descendant, self.compound()?)];
+        loop {
+            let spaced = self.skip_whitespace();
+            let combinator = if self.eat('>') {
+                self.skip_whitespace();
+                Combinator::Child
+            } else if spaced && self.peek().map(|c| c != ',').unwrap_or(false) {
+                Combinator::Descendant
+            } else {
+                return Ok(Complex(compounds));
+            };
+            compounds.push((combinator, self.compound()?));
+        }
+    }
+    fn compound(&mut self) -> Result<Compound, SelectorError> {
+        let start = self.position;
+        let mut compound = Compound::default();
+        if self.eat('*') {
+        } else if self.peek().map(|c| c.is_alphanumeric()).unwrap_or(false) {
+            compound.tag = Some(self.ident(false)?);
+        }
+        loop {
+            if self.eat('.') {
+                compound.filters.push(Filter::Class(self.ident(false)?));
+            } else if self.eat('[') {
+                compound.filters.push(self.attribute()?);
+            } else if self.eat(':') {
+                compound.filters.push(self.pseudo_class()?);
+            } else if self.peek() == Some('#') {
+                return self.error("ids are generated; select by tag, class or attribute");
+            } else {
+                break;
+            }
+        }
+        if self.position == start {
+            return self.error("expected a tag, class, attribute or pseudo-class");
+        }
+        Ok(compound)
+    }
+    fn attribute(&mut self) -> Result<Filter, SelectorError> {
+        self.skip_whitespace();
+        let key = self.ident(true)?;
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(Filter::Attribute(key, None));
+        }
+        let op = if self.eat('=') {
+            AttributeOp::Equals
+        } else {
+            let op = match self.peek() {
+                Some('^') => AttributeOp::Prefix,
+                Some('$') => AttributeOp::Suffix,
+                Some('*') => AttributeOp::Substring,
+                Some('~') => AttributeOp::Word,
+                _ => return self.error("expected `]` or an operator"),
+            };
+            self.position += 1;
+            if !self.eat('=') {
+                return self.error("expected `=`");
+            }
+            op
+        };
+        self.skip_whitespace();
+        let value = self.value()?;
+        self.skip_whitespace();
+        if !self.eat(']') {
+            return self.error("expected `]`");
+        }
+        Ok(Filter::Attribute(key, Some((op, value))))
+    }
+    fn pseudo_class(&mut self) -> Result<Filter, SelectorError> {
+        let start = self.position;
+        let name = self.ident(false)?;
+        if !self.eat('(') {
+            return self.error("expected `(`");
+        }
+        self.skip_whitespace();
+        let value = self.value()?;
+        self.skip_whitespace();
+        if !self.eat(')') {
+            return self.error("expected `)`");
+        }
+        match name.as_str() {
+            "text" => Ok(Filter::Text(value)),
+            "contains" => Ok(Filter::Contains(value)),
+            _ => {
+                self.position = start;
+                self.error("unknown pseudo-class; expected `:text` or `:contains`")
+            }
+        }
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// QUERIES
+///////////////////////////////////////////////////////////////////////////////
+
+/// Whether a node matches, given the elements above it, nearest last.
+type NodePredicate<'p, Msg> = Fn(&Html<Msg>, &[&Html<Msg>])->bool + 'p;
+/// Sees each node of a walk with the elements above it, nearest last.
+type Visit<'a, 'v, Msg> = FnMut(&'a Html<Msg>, &[&'a Html<Msg>]) + 'v;
+
+impl<Msg: Clone + Debug> Html<Msg> {
+    /// Every element in this tree, itself included, matching `selector`, in
+    /// document order. Only forced lazy nodes are searched.
+    ///
+    /// Panics if `selector` is invalid; see `Selector::parse` to handle that.
+    pub fn query_all(&self, selector: &str) -> Vec<&Html<Msg>> {
+        let selector = Selector::parse(selector).unwrap_or_else(|err| panic!("{}", err));
+        self.select_all(&selector)
+    }
+    /// The first element `query_all` would return.
+    pub fn query(&self, selector: &str) -> Option<&Html<Msg>> {
+        self.query_all(selector).into_iter().next()
+    }
+    pub fn select_all(&self, selector: &Selector) -> Vec<&Html<Msg>> {
+        self.filter_nodes(&|node, ancestors| selector.matches(node, ancestors))
+    }
+    /// Every node, elements or not, for which `predicate` holds, in
+    /// document order.
+    pub fn find_all<F>(&self, predicate: F) -> Vec<&Html<Msg>>
+    where
+        F: Fn(&Html<Msg>) -> bool
+    {
+        self.filter_nodes(&|node, _| predicate(node))
+    }
+    fn filter_nodes<'a>(&'a self, predicate: &NodePredicate<'_, Msg>) -> Vec<&'a Html<Msg>> {
+        let mut results: Vec<&Html<Msg>> = Vec::new();
+        let mut ancestors: Vec<&Html<Msg>> = Vec::new();
+        self.walk(&mut ancestors, &mut |node, ancestors| {
+            if predicate(node, ancestors) {
+                results.push(node);
+            }
+        });
+        results
+    }
+    /// Visits this subtree in document order; `ancestors` holds the elements
+    /// above each node, nearest last.
+    fn walk<'a>(
+        &'a self,
+        ancestors: &mut Vec<&'a Html<Msg>>,
+        visit: &mut Visit<'a, '_, Msg>,
+    ) {
+        visit(self, ancestors);
+        let is_element = self.tag().is_some();
+        if is_element {
+            ancestors.push(self);
+        }
+        for child in self.children() {
+            child.walk(ancestors, visit);
+        }
+        if is_element {
+            ancestors.pop();
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::collections::BTreeSet;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// DEFAULTS
+///////////////////////////////////////////////////////////////////////////////
+
+/// Elements dropped together with everything inside them.
+const DROPPED_WITH_CONTENTS: &[&str] = &[
+    "script", "style", "iframe", "frame", "frameset", "object", "embed",
+    "applet", "noscript", "template", "title", "textarea", "select",
+];
+
+const DEFAULT_TAGS: &[&str] = &[
+    "a", "abbr", "b", "blockquote", "br", "caption", "cite", "code", "col",
+    "colgroup", "dd", "del", "div", "dl", "dt", "em", "figcaption", "figure",
+    "h1", "h2", "h3", "h4", "h5", "h6", "hr", "i", "img", "ins", "kbd", "li",
+    "mark", "ol", "p", "pre", "q", "s", "small", "span", "strong", "sub",
+    "sup", "table", "tbody", "td", "tfoot", "th", "thead", "time", "tr", "u",
+    "ul",
+];
+
+const DEFAULT_ATTRIBUTES: &[&str] = &[
+    "alt", "cite", "class", "colspan", "datetime", "height", "href", "lang",
+    "rel", "rowspan", "src", "target", "title", "width",
+];
+
+/// Attributes holding URLs; checked for script schemes.
+const URL_ATTRIBUTES: &[&str] = &[
+    "action", "background", "cite", "formaction", "href", "poster", "src",
+    "xlink:href",
+];
+
+/// Schemes URL attributes may use; relative URLs are always allowed.
+const DEFAULT_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Named references decoded in attribute values, case-sensitive as in the
+/// spec. Only those that can spell out a URL scheme or its delimiters are
+/// needed; any other reference left before the scheme's end fails the check.
+const NAMED_REFERENCES: &[(&str, char)] = &[
+    ("amp", '&'), ("AMP", '&'), ("lt", '<'), ("LT", '<'), ("gt", '>'),
+    ("GT", '>'), ("quot", '"'), ("QUOT", '"'), ("apos", '\''),
+    ("nbsp", '\u{a0}'), ("colon", ':'), ("Tab", '\t'), ("NewLine", '\n'),
+    ("sol", '/'), ("quest", '?'), ("num", '#'), ("period", '.'),
+    ("lpar", '('), ("rpar", ')'), ("semi", ';'), ("excl", '!'),
+];
+
+/// Named references also recognised without their `;`.
+const LEGACY_REFERENCES: &[&str] = &[
+    "amp", "AMP", "lt", "LT", "gt", "GT", "quot", "QUOT", "nbsp",
+];
+
+
+///////////////////////////////////////////////////////////////////////////////
+// SANITIZER
+///////////////////////////////////////////////////////////////////////////////
+
+/// Allow-list filter for untrusted markup, e.g. CMS content shown through
+/// `Html::Raw`. Tags outside the list are unwrapped (their text is kept),
+/// event-handler attributes are always removed, and so are URLs whose
+/// scheme isn't allowed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Sanitizer {
+    pub allowed_tags: BTreeSet<String>,
+    pub allowed_attributes: BTreeSet<String>,
+    /// Lowercase, without the `:`.
+    pub allowed_schemes: BTreeSet<String>,
+}
+
+impl Default for Sanitizer {
+    fn default() -> Self {
+        Sanitizer {
+            allowed_tags: DEFAULT_TAGS.iter().map(|x| x.to_string()).collect(),
+            allowed_attributes: DEFAULT_ATTRIBUTES.iter().map(|x| x.to_string()).collect(),
+            allowed_schemes: DEFAULT_SCHEMES.iter().map(|x| x.to_string()).collect(),
+        }
+    }
+}
+
+impl Sanitizer {
+    pub fn new() -> Self {
+        Sanitizer::default()
+    }
+    pub fn allow_tag(mut self, tag: &str) -> Self {
+        self.allowed_tags.insert(tag.to_lowercase());
+        self
+    }
+    pub fn allow_attribute(mut self, key: &str) -> Self {
+        self.allowed_attributes.insert(key.to_lowercase());
+        self
+    }
+    /// E.g. `"tel"`; the default is `http`, `https` and `mailto`.
+    pub fn allow_scheme(mut self, scheme: &str) -> Self {
+        self.allowed_schemes.insert(scheme.trim_end_matches(':').to_lowercase());
+        self
+    }
+    pub fn sanitize(&self, markup: &str) -> String {
+        let mut output = String::new();
+        let mut tokens = Tokenizer::new(markup);
+        while let Some(token) = tokens.next() {
+            match token {
+                Token::Text(value) => {
+                    output.push_str(&value);
+                }
+                Token::StartTag{name, attributes, self_closing} => {
+                    if DROPPED_WITH_CONTENTS.contains(&name.as_str()) {
+                        if !self_closing {
+                            tokens.skip_until_end_tag(&name);
+                        }
+                    } else if self.allowed_tags.contains(&name) {
+                        output.push('<');
+                        output.push_str(&name);
+                        for (key, value) in attributes {
+                            if self.attribute_is_safe(&key, &value) {
+                                output.push_str(&format!(
+                                    " {key}=\"{value}\"",
+                                    key=key,
+                                    value=escape_attribute(&value),
+                                ));
+                            }
+                        }
+                        if self_closing {
+                            output.push_str(" /");
+                        }
+                        output.push('>');
+                    }
+                }
+                Token::EndTag(name) => {
+                    if self.allowed_tags.contains(&name) {
+                        output.push_str(&format!("</{}>", name));
+                    }
+                }
+            }
+        }
+        output
+    }
+    fn attribute_is_safe(&self, key: &str, value: &str) -> bool {
+        if key.starts_with("on") || !self.allowed_attributes.contains(key) {
+            return false;
+        }
+        if URL_ATTRIBUTES.contains(&key) {
+            return self.url_is_safe(value);
+        }
+        true
+    }
+    /// Decodes the value as the browser will, then requires an allowed
+    /// scheme or a relative URL.
+    fn url_is_safe(&self, value: &str) -> bool {
+        let url: String = decode_entities(value)
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+            .collect::<String>()
+            .to_lowercase();
+        match url.find([':', '/', '?', '#']) {
+            Some(ix) if url[ix..].starts_with(':') => {
+                self.allowed_schemes.contains(&url[..ix])
+            }
+            // RELATIVE - UNLESS A REFERENCE WE DON'T DECODE COULD HIDE A `:`
+            Some(ix) => !url[..ix].contains('&'),
+            None => !url.contains('&'),
+        }
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// INTERNAL - TOKENIZER
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Text(String),
+    StartTag {
+        name: String,
+        attributes: Vec<(String, String)>,
+        self_closing: bool,
+    },
+    EndTag(String),
+}
+
+/// Just enough of an HTML tokenizer to re-emit markup safely. Comments,
+/// doctypes and processing instructions are discarded; a `<` that does not
+/// open a tag is escaped.
+struct Tokenizer<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(source: &'a str) -> Self {
+        Tokenizer {source, position: 0}
+    }
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+    fn skip_past(&mut self, pattern: &str) {
+        match self.rest().find(pattern) {
+            Some(ix) => self.position += ix + pattern.len(),
+            None => self.position = self.source.len(),
+        }
+    }
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.position = self.source.len() - trimmed.len();
+    }
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(|c: char| !predicate(c)).unwrap_or(rest.len());
+        self.position += end;
+        &rest[..end]
+    }
+    fn skip_until_end_tag(&mut self, name: &str) {
+        let closing = format!("</{}", name);
+        match self.rest().to_ascii_lowercase().find(closing.as_str()) {
+            Some(ix) => {
+                self.position += ix;
+                self.skip_past(">");
+            }
+            None => self.position = self.source.len(),
+        }
+    }
+    fn next(&mut self) -> Option<Token> {
+        let rest = self.rest();
+        if rest.is_empty() {
+            return None;
+        }
+        if !rest.starts_with('<') {
+            let text = self.take_while(|c| c != '<');
+            return Some(Token::Text(text.to_owned()));
+        }
+        let after = rest[1..].chars().next();
+        if rest.starts_with("<!--") {
+            self.skip_past("-->");
+            self.next()
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            self.skip_past(">");
+            self.next()
+        } else if rest.starts_with("</") {
+            self.position += 2;
+            let name = self.take_while(|c| c.is_alphanumeric() || c == '-' || c == ':').to_lowercase();
+            self.skip_past(">");
+            Some(Token::EndTag(name))
+        } else if after.map(|c| c.is_ascii_alphabetic()).unwrap_or(false) {
+            self.position += 1;
+            Some(self.start_tag())
+        } else {
+            self.position += 1;
+            Some(Token::Text(String::from("&lt;")))
+        }
+    }
+    fn start_tag(&mut self) -> Token {
+        let name = self.take_while(|c| c.is_alphanumeric() || c == '-' || c == ':').to_lowercase();
+        let mut attributes: Vec<(String, String)> = Vec::new();
+        let mut self_closing = false;
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.is_empty() {
+                break;
+            } else if rest.starts_with('>') {
+                self.position += 1;
+                break;
+            } else if rest.starts_with("/>") {
+                self.position += 2;
+                self_closing = true;
+                break;
+            } else if rest.starts_with('/') {
+                self.position += 1;
+                continue;
+            }
+            let key = self.take_while(|c| {
+                !c.is_whitespace() && c != '=' && c != '>' && c != '/'
+            }).to_lowercase();
+            self.skip_whitespace();
+            let mut value = String::new();
+            if self.rest().starts_with('=') {
+                self.position += 1;
+                self.skip_whitespace();
+                let rest = self.rest();
+                if rest.starts_with('"') || rest.starts_with('\'') {
+                    let quote = &rest[..1];
+                    self.position += 1;
+                    let end = self.rest().find(quote).unwrap_or(self.rest().len());
+                    value = self.rest()[..end].to_owned();
+                    self.position += end;
+                    self.skip_past(quote);
+                } else {
+                    value = self.take_while(|c| !c.is_whitespace() && c != '>').to_owned();
+                }
+            }
+            if !key.is_empty() {
+                attributes.push((key, value));
+            }
+        }
+        Token::StartTag {
+            name,
+            attributes,
+            self_closing,
+        }
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// INTERNAL UTILS
+///////////////////////////////////////////////////////////////////////////////
+
+/// Decodes character references in an attribute value the way the HTML
+/// spec does: numeric references of any length with an optional `;`, and
+/// case-sensitive named references, the legacy ones also without `;`.
+/// Named references outside `NAMED_REFERENCES` are left as written.
+/// `template` decodes text with it too.
+pub(crate) fn decode_entities(value: &str) -> String {
+    let mut output = String::new();
+    let mut rest = value;
+    while let Some(ix) = rest.find('&') {
+        output.push_str(&rest[..ix]);
+        rest = &rest[ix + 1..];
+        let decoded = match rest.strip_prefix('#') {
+            Some(numeric) => decode_numeric(numeric).map(|(c, len)| (c, len + 1)),
+            None => decode_named(rest),
+        };
+        match decoded {
+            Some((c, len)) => {
+                output.push(c);
+                rest = &rest[len..];
+            }
+            None => output.push('&'),
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// The character and the length consumed, after the `&#`.
+fn decode_numeric(rest: &str) -> Option<(char, usize)> {
+    let (radix, prefix) = if rest.starts_with('x') || rest.starts_with('X') {
+        (16, 1)
+    } else {
+        (10, 0)
+    };
+    let digits = rest[prefix..]
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(rest.len() - prefix);
+    if digits == 0 {
+        return None;
+    }
+    // OUT OF RANGE VALUES BECOME U+FFFD, HOWEVER MANY DIGITS
+    let code = rest[prefix..prefix + digits]
+        .chars()
+        .try_fold(0u32, |code, c| {
+            code.checked_mul(radix).and_then(|x| x.checked_add(c.to_digit(radix).unwrap()))
+        })
+        .filter(|code| *code != 0)
+        .and_then(std::char::from_u32)
+        .unwrap_or('\u{fffd}');
+    let mut len = prefix + digits;
+    if rest[len..].starts_with(';') {
+        len += 1;
+    }
+    Some((code, len))
+}
+
+/// The character and the length consumed, after the `&`.
+fn decode_named(rest: &str) -> Option<(char, usize)> {
+    let name_len = rest
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(rest.len());
+    let name = &rest[..name_len];
+    let lookup = |name: &str| NAMED_REFERENCES.iter().find(|x| x.0 == name).map(|x| x.1);
+    if rest[name_len..].starts_with(';') {
+        if let Some(c) = lookup(name) {
+            return Some((c, name_len + 1));
+        }
+    }
+    // IN ATTRIBUTES, `&ampx` AND `&amp=` STAY AS WRITTEN
+    let next = rest[name_len..].chars().next();
+    if LEGACY_REFERENCES.contains(&name) && next != Some('=') {
+        return lookup(name).map(|c| (c, name_len));
+    }
+    None
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
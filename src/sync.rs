@@ -13,18 +13,26 @@ use std::sync::RwLock;
 use std::rc::Rc;
 use either::Either;
 use serde::{self, Serialize, Deserialize};
-use web_sys::console;
-use wasm_bindgen::JsValue;
-use wasm_bindgen::closure;
-use wasm_bindgen::closure::Closure;
 
 use crate::html;
 
-#[derive(Debug, PartialEq, Clone, Hash)]
+#[derive(Debug, PartialEq, Clone, Hash, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "Msg: Clone + Debug + Serialize",
+    deserialize = "Msg: Clone + Debug + Deserialize<'de> + 'static",
+))]
 pub enum Patch<Msg> {
     SetChildText {parent_id: String, value: String},
     SetNode {id: String, value: html::Html<Msg>},
     SetChildren {id: String, value: Vec<html::Html<Msg>>},
+    SetAttributes {id: String, value: Vec<html::Attribute>},
+    SetStyling {id: String, value: Vec<html::Style>},
+    SetEvents {id: String, value: BTreeMap<String, html::Handler<Msg>>},
+    SetProperty {id: String, key: String, value: html::PropertyValue},
+    /// `key` is no longer set by the view; the element gets `reset`.
+    RemoveProperty {id: String, key: String, reset: html::PropertyValue},
+    SetNodeRef {id: String, value: Option<html::NodeRef>},
+    SetLifecycle {id: String, value: html::Lifecycle<Msg>},
 }
 
 impl<Msg> Patch<Msg> {
@@ -33,6 +41,13 @@ impl<Msg> Patch<Msg> {
             Patch::SetChildText{parent_id, ..} => Some(parent_id.clone()),
             Patch::SetNode{id, ..} => Some(id.clone()),
             Patch::SetChildren{id, ..} => Some(id.clone()),
+            Patch::SetAttributes{id, ..} => Some(id.clone()),
+            Patch::SetStyling{id, ..} => Some(id.clone()),
+            Patch::SetEvents{id, ..} => Some(id.clone()),
+            Patch::SetProperty{id, ..} => Some(id.clone()),
+            Patch::RemoveProperty{id, ..} => Some(id.clone()),
+            Patch::SetNodeRef{id, ..} => Some(id.clone()),
+            Patch::SetLifecycle{id, ..} => Some(id.clone()),
         }
     }
 }
@@ -0,0 +1,392 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::html::*;
+use crate::platform::JsValue;
+use crate::sanitize::decode_entities;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// BINDINGS
+///////////////////////////////////////////////////////////////////////////////
+
+/// The Rust values a template's placeholders stand for:
+/// - `on:click="{{save}}"` sends the message of the handler bound to `save`;
+/// - `{{items}}` in text is replaced by the nodes of the slot `items`.
+pub struct Bindings<Msg> {
+    handlers: HashMap<String, Handler<Msg>>,
+    slots: HashMap<String, Rc<Fn()->Html<Msg>>>,
+}
+
+impl<Msg: Clone + Debug + 'static> Bindings<Msg> {
+    pub fn new() -> Self {
+        Bindings {
+            handlers: HashMap::new(),
+            slots: HashMap::new(),
+        }
+    }
+    /// Binds `name` to a handler that always sends `msg`; see
+    /// `Handler::message`.
+    pub fn message(mut self, name: &str, msg: Msg) -> Self {
+        self.handlers.insert(name.to_owned(), Handler::message(msg));
+        self
+    }
+    pub fn handler<F>(mut self, name: &str, fun: F) -> Self
+    where
+        F: Fn(JsValue)->Msg + 'static
+    {
+        self.handlers.insert(name.to_owned(), Handler::new(Rc::new(fun)));
+        self
+    }
+    /// Binds `name` to the nodes `view` builds. It is called for each use of
+    /// the placeholder, so every copy gets ids of its own.
+    pub fn slot<F>(mut self, name: &str, view: F) -> Self
+    where
+        F: Fn()->Html<Msg> + 'static
+    {
+        self.slots.insert(name.to_owned(), Rc::new(view));
+        self
+    }
+}
+
+impl<Msg: Clone + Debug + 'static> Default for Bindings<Msg> {
+    fn default() -> Self {
+        Bindings::new()
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// ERRORS
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TemplateError {
+    /// Starting at 1.
+    pub line: usize,
+    /// In characters, starting at 1.
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// PARSER
+///////////////////////////////////////////////////////////////////////////////
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta",
+    "source", "track", "wbr",
+];
+
+/// Elements whose contents are not markup.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Builds the view described by `source`: a single element, or a fragment
+/// when there are several top-level nodes. The result is an ordinary view,
+/// diffed and patched like any other.
+///
+/// Inline `style` declarations become styling, as with `view!`, and the
+/// contents of `script` and `style` elements are kept as raw markup. Text
+/// that is only whitespace, e.g. indentation, is dropped. Write `\{{` for a
+/// literal `{{`. As in HTML, tag names are lowercased outside `svg`, and
+/// closing tags match whatever their case.
+pub fn parse<Msg>(source: &str, bindings: &Bindings<Msg>) -> Result<Html<Msg>, TemplateError>
+where
+    Msg: Clone + Debug + 'static
+{
+    let mut parser = Parser {source, position: 0, bindings, in_svg: false};
+    let mut nodes = parser.nodes(None)?;
+    if nodes.len() == 1 {
+        Ok(nodes.remove(0))
+    } else {
+        Ok(Html::new_fragment(nodes))
+    }
+}
+
+struct Parser<'a, Msg> {
+    source: &'a str,
+    position: usize,
+    bindings: &'a Bindings<Msg>,
+    /// Inside `svg`, where tag names such as `foreignObject` keep their case.
+    in_svg: bool,
+}
+
+impl<'a, Msg: Clone + Debug + 'static> Parser<'a, Msg> {
+    fn error_at<T>(&self, position: usize, message: String) -> Result<T, TemplateError> {
+        let before = &self.source[..position];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|x| x + 1).unwrap_or(0);
+        Err(TemplateError {
+            line,
+            column: before[line_start..].chars().count() + 1,
+            message,
+        })
+    }
+    fn error<T>(&self, message: &str) -> Result<T, TemplateError> {
+        self.error_at(self.position, message.to_owned())
+    }
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+    fn eat(&mut self, prefix: &str) -> bool {
+        if self.rest().starts_with(prefix) {
+            self.position += prefix.len();
+            true
+        } else {
+            false
+        }
+    }
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.position;
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.position += c.len_utf8();
+        }
+        self.position > start
+    }
+    /// Everything up to `end`, which is consumed too.
+    fn until(&mut self, end: &str, what: &str) -> Result<&'a str, TemplateError> {
+        match self.rest().find(end) {
+            Some(offset) => {
+                let value = &self.rest()[..offset];
+                self.position += offset + end.len();
+                Ok(value)
+            }
+            None => self.error(&format!("unterminated {}", what)),
+        }
+    }
+    fn name(&mut self, what: &str) -> Result<String, TemplateError> {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '-' || c == '_' || c == ':' || c == '.' {
+                self.position += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+        if self.position == start {
+            return self.error(&format!("expected {}", what));
+        }
+        Ok(self.source[start..self.position].to_owned())
+    }
+    /// Nodes up to the closing tag of `parent`, or to the end of the source
+    /// at the top level.
+    fn nodes(&mut self, parent: Option<(&str, usize)>) -> Result<Vec<Html<Msg>>, TemplateError> {
+        let mut nodes: Vec<Html<Msg>> = Vec::new();
+        loop {
+            let start = self.position;
+            if self.rest().is_empty() {
+                return match parent {
+                    Some((tag, open)) => self.error_at(open, format!("unclosed <{}>", tag)),
+                    None => Ok(nodes),
+                };
+            } else if self.eat("</") {
+                let tag = self.name("a tag name")?;
+                self.skip_whitespace();
+                if !self.eat(">") {
+                    return self.error("expected `>`");
+                }
+                return match parent {
+                    Some((open_tag, _)) if open_tag.eq_ignore_ascii_case(&tag) => Ok(nodes),
+                    Some((open_tag, _)) => {
+                        self.error_at(start, format!("expected </{}>, found </{}>", open_tag, tag))
+                    }
+                    None => self.error_at(start, format!("unexpected </{}>", tag)),
+                };
+            } else if self.eat("<!--") {
+                let value = self.until("-->", "comment")?;
+                nodes.push(Html::new_comment(value.to_owned()));
+            } else if self.eat("<!") {
+                self.until(">", "doctype")?;
+            } else if self.rest().starts_with('<') {
+                nodes.push(self.element()?);
+            } else {
+                self.text(&mut nodes)?;
+            }
+        }
+    }
+    /// Text up to the next tag, split around `{{slot}}` placeholders.
+    fn text(&mut self, nodes: &mut Vec<Html<Msg>>) -> Result<(), TemplateError> {
+        let end = self.rest().find('<').unwrap_or(self.rest().len());
+        let text_end = self.position + end;
+        let mut value = String::new();
+        while self.position < text_end {
+            let chunk = &self.source[self.position..text_end];
+            match chunk.find("{{") {
+                Some(ix) if chunk[..ix].ends_with('\\') => {
+                    value.push_str(&decode_entities(&chunk[..ix - 1]));
+                    value.push_str("{{");
+                    self.position += ix + 2;
+                }
+                Some(0) => {
+                    push_text(nodes, &mut value);
+                    let start = self.position;
+                    self.position += 2;
+                    let name = self.placeholder(text_end)?;
+                    match self.bindings.slots.get(&name) {
+                        Some(view) => nodes.push(view.as_ref()()),
+                        None => return self.error_at(start, format!("no slot bound to {{{{{}}}}}", name)),
+                    }
+                }
+                found => {
+                    let length = found.unwrap_or(chunk.len());
+                    value.push_str(&decode_entities(&chunk[..length]));
+                    self.position += length;
+                }
+            }
+        }
+        push_text(nodes, &mut value);
+        Ok(())
+    }
+    /// The name in a placeholder whose `{{` was consumed, before `limit`.
+    fn placeholder(&mut self, limit: usize) -> Result<String, TemplateError> {
+        let start = self.position;
+        match self.source[start..limit].find("}}") {
+            Some(offset) => {
+                let name = self.source[start..start + offset].trim().to_owned();
+                if name.is_empty() {
+                    return self.error("empty placeholder");
+                }
+                self.position += offset + 2;
+                Ok(name)
+            }
+            None => self.error("unterminated placeholder; expected `}}`"),
+        }
+    }
+    fn element(&mut self) -> Result<Html<Msg>, TemplateError> {
+        let open = self.position;
+        self.position += 1;
+        let mut tag = self.name("a tag name")?;
+        if !self.in_svg {
+            tag = tag.to_ascii_lowercase();
+        }
+        let mut node: Html<Msg> = Html::new_node(tag.clone());
+        loop {
+            let spaced = self.skip_whitespace();
+            if self.eat("/>") {
+                return Ok(node);
+            } else if self.eat(">") {
+                break;
+            } else if self.rest().is_empty() {
+                return self.error_at(open, format!("unclosed <{}", tag));
+            } else if !spaced {
+                return self.error("expected whitespace, `>` or `/>`");
+            }
+            self.attribute(&mut node)?;
+        }
+        if VOID_ELEMENTS.contains(&tag.as_str()) {
+            return Ok(node);
+        }
+        if RAW_TEXT_ELEMENTS.contains(&tag.as_str()) {
+            let close = format!("</{}>", tag);
+            let contents = self.until(close.as_str(), &format!("<{}>", tag))?;
+            if !contents.is_empty() {
+                node.add_child(Html::new_raw(contents.to_owned()));
+            }
+            return Ok(node);
+        }
+        let in_svg = self.in_svg;
+        self.in_svg = match tag.as_str() {
+            "svg" => true,
+            "foreignObject" => false,
+            _ => in_svg,
+        };
+        let children = self.nodes(Some((tag.as_str(), open)));
+        self.in_svg = in_svg;
+        for child in children? {
+            node.add_child(child);
+        }
+        Ok(node)
+    }
+    fn attribute(&mut self, node: &mut Html<Msg>) -> Result<(), TemplateError> {
+        let start = self.position;
+        let key = self.name("an attribute name")?;
+        self.skip_whitespace();
+        if !self.eat("=") {
+            if key.starts_with("on:") {
+                return self.error_at(start, format!("{} needs a handler, e.g. {}=\"{{{{name}}}}\"", key, key));
+            }
+            node.add_attribute(Attribute::Toggle{key, value: true});
+            return Ok(());
+        }
+        self.skip_whitespace();
+        let value_start = self.position;
+        let value = match self.peek() {
+            Some(quote @ '"') | Some(quote @ '\'') => {
+                self.position += 1;
+                let mut end = String::new();
+                end.push(quote);
+                self.until(end.as_str(), "attribute value")?.to_owned()
+            }
+            _ => {
+                let length = self
+                    .rest()
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(self.rest().len());
+                if length == 0 {
+                    return self.error("expected an attribute value");
+                }
+                let value = self.rest()[..length].to_owned();
+                self.position += length;
+                value
+            }
+        };
+        if let Some(event_name) = key.strip_prefix("on:") {
+            let event_name = event_name.to_owned();
+            let name = value.trim();
+            if !(name.starts_with("{{") && name.ends_with("}}")) || name.len() < 5 {
+                return self.error_at(value_start, format!("{} takes a placeholder, e.g. {}=\"{{{{name}}}}\"", key, key));
+            }
+            let name = name[2..name.len() - 2].trim();
+            match self.bindings.handlers.get(name) {
+                Some(handler) => node.add_handler(event_name, handler.clone()),
+                None => return self.error_at(value_start, format!("no handler bound to {{{{{}}}}}", name)),
+            }
+            return Ok(());
+        }
+        if value.replace("\\{{", "").contains("{{") {
+            return self.error_at(value_start, String::from("placeholders in attribute values are only supported for on: attributes"));
+        }
+        let value = value.replace("\\{{", "{{");
+        if key == "style" {
+            for declaration in value.split(';') {
+                if declaration.trim().is_empty() {
+                    continue;
+                }
+                match declaration.find(':') {
+                    Some(colon) => node.add_style(Style::Style {
+                        property: declaration[..colon].trim().replace("-", "_"),
+                        value: decode_entities(declaration[colon + 1..].trim()),
+                    }),
+                    None => {
+                        return self.error_at(value_start, format!("expected `property: value` in style, found {:?}", declaration.trim()));
+                    }
+                }
+            }
+        } else {
+            node.add_attribute(Attribute::Pair{key, value: decode_entities(&value)});
+        }
+        Ok(())
+    }
+}
+
+/// Adds the text collected so far, unless it is only whitespace.
+fn push_text<Msg>(nodes: &mut Vec<Html<Msg>>, value: &mut String) {
+    let value = std::mem::take(value);
+    if !value.trim().is_empty() {
+        nodes.push(Html::Text{value});
+    }
+}
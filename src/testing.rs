@@ -0,0 +1,274 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::fs;
+use std::path::Path;
+use crate::platform::JsValue;
+
+use crate::core::Component;
+use crate::html::*;
+use crate::query::Selector;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// FINDERS
+///////////////////////////////////////////////////////////////////////////////
+
+/// Picks the element a synthetic event is dispatched to. The first match in
+/// document order wins; for `Text`, the innermost element whose text is the
+/// given string.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Find {
+    Tag(String),
+    Text(String),
+    Attribute(String, String),
+    Selector(Selector),
+}
+
+impl Find {
+    pub fn tag(tag: &str) -> Self {
+        Find::Tag(tag.to_owned())
+    }
+    pub fn text(text: &str) -> Self {
+        Find::Text(text.to_owned())
+    }
+    pub fn attribute(key: &str, value: &str) -> Self {
+        Find::Attribute(key.to_owned(), value.to_owned())
+    }
+    /// Panics if `selector` is invalid.
+    pub fn selector(selector: &str) -> Self {
+        Find::Selector(Selector::parse(selector).unwrap_or_else(|err| panic!("{}", err)))
+    }
+    fn matches<Msg: Clone + Debug>(&self, node: &Html<Msg>) -> bool {
+        match (self, node) {
+            (Find::Tag(tag), Html::Node{tag: node_tag, ..}) => tag == node_tag,
+            (Find::Text(text), Html::Node{..}) => &node.text_content() == text,
+            (Find::Attribute(key, value), Html::Node{attributes, ..}) => {
+                attributes.iter().any(|attribute| {
+                    &attribute.key() == key && attribute.value().as_ref() == Some(value)
+                })
+            }
+            _ => false,
+        }
+    }
+    /// Path from `node` down to the matching element, inclusive.
+    fn path<'a, Msg: Clone + Debug>(&self, node: &'a Html<Msg>) -> Option<Vec<&'a Html<Msg>>> {
+        if let Find::Selector(selector) = self {
+            let target = node.select_all(selector).into_iter().next()?;
+            return path_to(node, target);
+        }
+        let innermost = matches!(self, Find::Text(_));
+        if !innermost && self.matches(node) {
+            return Some(vec![node]);
+        }
+        for child in node.children() {
+            if let Some(mut path) = self.path(child) {
+                path.insert(0, node);
+                return Some(path);
+            }
+        }
+        if innermost && self.matches(node) {
+            return Some(vec![node]);
+        }
+        None
+    }
+}
+
+fn path_to<'a, Msg: Clone + Debug>(node: &'a Html<Msg>, target: &Html<Msg>) -> Option<Vec<&'a Html<Msg>>> {
+    if std::ptr::eq(node, target) {
+        return Some(vec![node]);
+    }
+    for child in node.children() {
+        if let Some(mut path) = path_to(child, target) {
+            path.insert(0, node);
+            return Some(path);
+        }
+    }
+    None
+}
+
+impl fmt::Display for Find {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Find::Tag(tag) => write!(f, "<{}>", tag),
+            Find::Text(text) => write!(f, "text {:?}", text),
+            Find::Attribute(key, value) => write!(f, "[{}={:?}]", key, value),
+            Find::Selector(selector) => write!(f, "{:?}", selector.to_string()),
+        }
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// HARNESS
+///////////////////////////////////////////////////////////////////////////////
+
+/// Runs a `Component` without a browser: `view` builds plain vnodes and
+/// events are dispatched to handlers directly, so no DOM or JS is involved.
+/// Handlers receive `JsValue::UNDEFINED` as their event.
+pub struct Harness<Model, Msg>
+where
+    Model: Debug + PartialEq + Clone + Hash,
+    Msg: Debug + PartialEq + Clone + Hash
+{
+    spec: Component<Model, Msg>,
+    view: Html<Msg>,
+}
+
+impl<Model, Msg> Harness<Model, Msg>
+where
+    Model: Debug + PartialEq + Clone + Hash,
+    Msg: Debug + PartialEq + Clone + Hash
+{
+    pub fn new(spec: Component<Model, Msg>) -> Self {
+        let view = Harness::build_view(&spec);
+        Harness {spec, view}
+    }
+    fn build_view(spec: &Component<Model, Msg>) -> Html<Msg> {
+        let mut view = spec.view.as_ref()(&spec.model.borrow());
+        view.force_all();
+        view
+    }
+    pub fn model(&self) -> Model {
+        self.spec.model.borrow().clone()
+    }
+    pub fn view(&self) -> &Html<Msg> {
+        &self.view
+    }
+    pub fn html(&self) -> String {
+        self.view.to_html_string()
+    }
+    pub fn send(&mut self, msg: Msg) {
+        self.spec.update.as_ref()(&mut self.spec.model.borrow_mut(), msg);
+        self.view = Harness::build_view(&self.spec);
+    }
+    /// Fires `event_name` on the element `find` picks. Like the DOM, the
+    /// event bubbles: handlers on the element and then on each ancestor run.
+    /// Panics when nothing matches or no handler listens for the event.
+    pub fn dispatch(&mut self, find: Find, event_name: &str) {
+        self.dispatch_with(find, event_name, JsValue::UNDEFINED)
+    }
+    /// `dispatch`, handing each handler `value` as its event, e.g. the text
+    /// of an input.
+    pub fn dispatch_with(&mut self, find: Find, event_name: &str, value: JsValue) {
+        let messages: Vec<Msg> = {
+            let path = find
+                .path(&self.view)
+                .unwrap_or_else(|| panic!("no element matches {}", find));
+            let mut handlers: Vec<_> = path.iter()
+                .rev()
+                .filter_map(|node| node.events()?.get(event_name))
+                .collect();
+            // THE LAST HANDLER TAKES `value`; CLONING A BROWSER VALUE OUTSIDE
+            // WASM PANICS, SO THE COMMON SINGLE-HANDLER CASE NEVER DOES
+            let last = handlers.pop();
+            let mut messages: Vec<Msg> = handlers.into_iter()
+                .filter_map(|handler| handler.eval(value.clone()))
+                .collect();
+            messages.extend(last.and_then(|handler| handler.eval(value)));
+            messages
+        };
+        if messages.is_empty() {
+            panic!("no {:?} handler on {} or its ancestors", event_name, find);
+        }
+        for msg in messages {
+            self.send(msg);
+        }
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// SNAPSHOTS
+///////////////////////////////////////////////////////////////////////////////
+
+/// Checks `html.to_pretty_string()` against `tests/snapshots/{name}.snap` in
+/// the calling crate; see `testing::assert_snapshot`.
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $html:expr) => {
+        $crate::testing::assert_snapshot(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests")
+                .join("snapshots")
+                .join(format!("{}.snap", $name)),
+            &$html.to_pretty_string(),
+        )
+    };
+}
+
+/// Compares `actual` with the snapshot file at `path`, panicking with a line
+/// diff when they differ, or when the snapshot is missing. With
+/// `UPDATE_SNAPSHOTS=1` set, missing and mismatching snapshots are written
+/// instead, to be reviewed and checked in.
+pub fn assert_snapshot<P: AsRef<Path>>(path: P, actual: &str) {
+    let path = path.as_ref();
+    let update = std::env::var("UPDATE_SNAPSHOTS").map(|x| x == "1").unwrap_or(false);
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => expected.replace("\r\n", "\n"),
+        Err(_) if update => {
+            write_snapshot(path, actual);
+            eprintln!("wrote new snapshot {}", path.display());
+            return;
+        }
+        Err(_) => panic!(
+            "snapshot {} is missing\nrerun with UPDATE_SNAPSHOTS=1 to write it",
+            path.display(),
+        ),
+    };
+    if expected.trim_end() == actual.trim_end() {
+        return;
+    }
+    if update {
+        write_snapshot(path, actual);
+        eprintln!("updated snapshot {}", path.display());
+        return;
+    }
+    panic!(
+        "snapshot {} does not match (- expected, + actual):\n{}\nrerun with UPDATE_SNAPSHOTS=1 to accept",
+        path.display(),
+        line_diff(expected.trim_end(), actual.trim_end()),
+    );
+}
+
+fn write_snapshot(path: &Path, contents: &str) {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).expect("unable to create snapshot directory");
+    }
+    let contents = format!("{}\n", contents.trim_end());
+    fs::write(path, contents).expect("unable to write snapshot");
+}
+
+/// Unified-style diff of two texts by line, from their longest common
+/// subsequence of lines. Snapshots are small, so quadratic is fine.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+    // common[i][j] - LENGTH OF THE LCS OF old[i..] AND new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut out: Vec<String> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out.push(format!("  {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            out.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            out.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    out.join("\n")
+}
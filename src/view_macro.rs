@@ -5,8 +5,6 @@ use std::collections::HashMap;
 use std::cell::{self, Cell, RefCell};
 use std::rc::Rc;
 use either::Either;
-use web_sys::console;
-use wasm_bindgen::JsValue;
 
 use crate::css;
 use crate::css::CssValue;
@@ -36,6 +34,25 @@ macro_rules! attribute_value {
             value: $val.to_owned(),
         }
     };
+    // NAMESPACED OR HYPHENATED - E.G. "xlink:href"
+    ($key:literal, true) => {
+        Attribute::Toggle{
+            key: String::from($key),
+            value: true,
+        }
+    };
+    ($key:literal, false) => {
+        Attribute::Toggle{
+            key: String::from($key),
+            value: false,
+        }
+    };
+    ($key:literal, $val:expr) => {
+        Attribute::Pair{
+            key: String::from($key),
+            value: $val.to_owned(),
+        }
+    };
 }
 
 
@@ -47,6 +64,19 @@ macro_rules! view_argument {
     ($node:expr, $key:ident = $val:tt) => {
         $node.add_attribute(attribute_value!($key, $val));
     };
+    ($node:expr, $key:literal = $val:tt) => {
+        $node.add_attribute(attribute_value!($key, $val));
+    };
+    
+    ///////////////////////////////////////////////////////////////////////////
+    // DOM PROPERTY
+    ///////////////////////////////////////////////////////////////////////////
+    ($node:expr, @ $key:ident = $val:expr) => {
+        $node.add_attribute(Attribute::Property{
+            key: String::from(stringify!($key)),
+            value: PropertyValue::from($val),
+        });
+    };
 
     ///////////////////////////////////////////////////////////////////////////
     // STYLE
@@ -84,6 +114,33 @@ macro_rules! view_argument {
             Rc::new($value),
         );
     };
+    // A FIXED MESSAGE - SERIALIZABLE, UNLIKE A CLOSURE
+    ($node:expr, . $key:ident [$value:expr]) => {
+        $node.add_event_message(
+            String::from(stringify!($key)),
+            $value,
+        );
+    };
+    
+    ///////////////////////////////////////////////////////////////////////////
+    // NODE REF
+    ///////////////////////////////////////////////////////////////////////////
+    ($node:expr, ref ($value:expr)) => {
+        $node.set_node_ref($value);
+    };
+    
+    ///////////////////////////////////////////////////////////////////////////
+    // LIFECYCLE HOOKS
+    ///////////////////////////////////////////////////////////////////////////
+    ($node:expr, on_mount ($value:expr)) => {
+        $node.set_on_mount(Rc::new($value));
+    };
+    ($node:expr, on_update ($value:expr)) => {
+        $node.set_on_update(Rc::new($value));
+    };
+    ($node:expr, on_unmount ($value:expr)) => {
+        $node.set_on_unmount(Rc::new($value));
+    };
     
     ///////////////////////////////////////////////////////////////////////////
     // CHILDREN
@@ -96,6 +153,10 @@ macro_rules! view_argument {
             }
         );
     };
+    // PREBUILT NODE - E.G. {Html::new_unmanaged(...)}
+    ($node:expr, {$value:expr}) => {
+        $node.add_child($value);
+    };
     // EMPTY NODE
     ($node:expr, $key:ident ()) => {
         $node.add_child(
@@ -162,6 +223,23 @@ macro_rules! view_arguments {
             $($rest)*
         );
     };
+    ($node:expr, $key:literal = $val:tt, $($rest:tt)*) => {
+        view_argument!($node, $key = $val);
+        view_arguments!(
+            $node,
+            $($rest)*
+        );
+    };
+    ///////////////////////////////////////////////////////////////////////////
+    // MANY - DOM PROPERTY
+    ///////////////////////////////////////////////////////////////////////////
+    ($node:expr, @ $key:ident = $val:expr, $($rest:tt)*) => {
+        view_argument!($node, @ $key = $val);
+        view_arguments!(
+            $node,
+            $($rest)*
+        );
+    };
     ///////////////////////////////////////////////////////////////////////////
     // MANY - CSS
     ///////////////////////////////////////////////////////////////////////////
@@ -203,6 +281,13 @@ macro_rules! view_arguments {
             $($rest)*
         );
     };
+    ($node:expr, {$value:expr}, $($rest:tt)*) => {
+        view_argument!($node, {$value});
+        view_arguments!(
+            $node,
+            $($rest)*
+        );
+    };
     
     ///////////////////////////////////////////////////////////////////////////
     // SINGLE
@@ -0,0 +1,137 @@
+//! The time-travel debugger: recording, jumping between states and resuming.
+
+use std::rc::Rc;
+use serde::{Serialize, Deserialize};
+use view_prototype::core::{Component, Process};
+use view_prototype::debugger::Session;
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+
+#[derive(Debug, PartialEq, Clone, Hash, Serialize, Deserialize)]
+enum Msg {
+    Add(u32),
+    Reset,
+}
+
+fn update(model: &mut u32, msg: Msg) {
+    match msg {
+        Msg::Add(n) => *model += n,
+        Msg::Reset => *model = 0,
+    }
+}
+
+fn view(model: &u32) -> Html<Msg> {
+    let mut node = Html::new_node(String::from("p"));
+    node.add_child(Html::Text{value: format!("total {}", model)});
+    node
+}
+
+fn process() -> (Process<u32, Msg>, Rc<MockDom>) {
+    let dom = Rc::new(MockDom::new());
+    let process = Process::with_backend(Component::new(0, update, view), dom.clone());
+    (process, dom)
+}
+
+fn shows(dom: &MockDom, total: u32) -> bool {
+    dom.body_html().contains(&format!(">total {}</p>", total))
+}
+
+#[test]
+fn session_positions_index_states() {
+    let mut session: Session<u32, Msg> = Session::new(0);
+    assert!(session.is_live());
+    session.record(Msg::Add(2), 2);
+    session.record(Msg::Add(3), 5);
+    assert_eq!(session.last_position(), 2);
+    assert_eq!(session.model_at(0), Some(&0));
+    assert_eq!(session.model_at(2), Some(&5));
+    assert_eq!(session.msg_at(0), None);
+    assert_eq!(session.msg_at(1), Some(&Msg::Add(2)));
+    assert_eq!(session.jump(1), Some(2));
+    assert!(!session.is_live());
+    // OUT OF RANGE LEAVES THE POSITION ALONE
+    assert_eq!(session.jump(3), None);
+    assert_eq!(session.position, 1);
+    assert_eq!(session.jump(2), Some(5));
+    assert!(session.is_live());
+}
+
+#[test]
+fn records_history_and_jumps_between_states() {
+    let (process, dom) = process();
+    process.start_debugger();
+    process.send(Msg::Add(2));
+    process.send(Msg::Add(3));
+    process.tick();
+    let session = process.debug_session().unwrap();
+    let history: Vec<(Msg, u32)> = session.entries.iter().map(|x| (x.msg.clone(), x.model)).collect();
+    assert_eq!(history, vec![(Msg::Add(2), 2), (Msg::Add(3), 5)]);
+    assert!(process.jump_to(1));
+    assert_eq!(process.model(), 2);
+    assert!(shows(&dom, 2));
+    assert!(process.step_back());
+    assert!(shows(&dom, 0));
+    assert!(!process.step_back());
+    assert!(!process.jump_to(7));
+    assert!(process.step_forward());
+    assert!(shows(&dom, 2));
+}
+
+#[test]
+fn messages_wait_while_a_past_state_is_shown() {
+    let (process, dom) = process();
+    process.start_debugger();
+    process.send(Msg::Add(2));
+    process.tick();
+    assert!(process.jump_to(0));
+    process.send(Msg::Add(10));
+    process.tick();
+    // THE PAST STATE STAYS ON SCREEN AND IN THE LOG
+    assert_eq!(process.model(), 0);
+    assert_eq!(process.debug_session().unwrap().entries.len(), 1);
+    assert!(process.resume());
+    assert!(shows(&dom, 2));
+    process.tick();
+    assert_eq!(process.model(), 12);
+    assert!(shows(&dom, 12));
+    assert_eq!(process.debug_session().unwrap().last_position(), 2);
+}
+
+#[test]
+fn stopping_returns_to_the_latest_state() {
+    let (process, dom) = process();
+    process.start_debugger();
+    process.send(Msg::Add(4));
+    process.tick();
+    process.jump_to(0);
+    process.send(Msg::Reset);
+    process.tick();
+    let session = process.stop_debugger().unwrap();
+    assert_eq!(session.entries.len(), 1);
+    assert!(shows(&dom, 4));
+    // QUEUED WHILE PAUSED, HANDLED ONCE RECORDING IS OFF
+    process.tick();
+    assert_eq!(process.model(), 0);
+    assert!(!process.resume());
+}
+
+#[test]
+fn sessions_import_at_their_position() {
+    let (exported, _) = process();
+    exported.start_debugger();
+    exported.send(Msg::Add(2));
+    exported.send(Msg::Add(3));
+    exported.tick();
+    exported.jump_to(1);
+    let json = exported.export_session().unwrap();
+    let (imported, dom) = process();
+    imported.import_session(&json).unwrap();
+    assert_eq!(imported.model(), 2);
+    assert!(shows(&dom, 2));
+    // HAND-EDITED, OR FROM A BROKEN EXPORT
+    let mut session: Session<u32, Msg> = serde_json::from_str(&json).unwrap();
+    session.position = 3;
+    assert!(imported.import_session(&serde_json::to_string(&session).unwrap()).is_err());
+    assert_eq!(imported.debug_session().unwrap().position, 1);
+    assert!(shows(&dom, 2));
+}
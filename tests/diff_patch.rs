@@ -0,0 +1,305 @@
+//! Property tests for `Html::sync`: patching a mounted tree into another must
+//! leave the document exactly as mounting the other tree from scratch would.
+#![cfg(feature = "ssr")]
+
+use std::collections::HashSet;
+use std::rc::Rc;
+use proptest::prelude::*;
+use proptest::collection::vec;
+use proptest::sample::select;
+
+use view_prototype::html::*;
+use view_prototype::mock_dom::{MockDom, MockId, MockKind};
+use view_prototype::platform::JsValue;
+
+type Msg = u8;
+
+
+///////////////////////////////////////////////////////////////////////////////
+// TREES
+///////////////////////////////////////////////////////////////////////////////
+
+/// Plain description of an `Html` tree; vnodes hold closures, so they are
+/// built from this only once a case runs.
+#[derive(Debug, Clone)]
+enum Tree {
+    Element {
+        tag: &'static str,
+        attributes: Vec<Attr>,
+        styles: Vec<Decl>,
+        events: Vec<(&'static str, Msg)>,
+        children: Vec<Tree>,
+    },
+    Unmanaged {
+        tag: &'static str,
+        key: &'static str,
+        attributes: Vec<Attr>,
+        styles: Vec<Decl>,
+    },
+    Fragment(Vec<Tree>),
+    Text(&'static str),
+    Comment(&'static str),
+    Raw(&'static str),
+}
+
+#[derive(Debug, Clone)]
+enum Attr {
+    Pair(&'static str, &'static str),
+    Toggle(&'static str, bool),
+}
+
+/// A declaration, optionally under a pseudo-class.
+#[derive(Debug, Clone)]
+struct Decl(Option<&'static str>, &'static str, &'static str);
+
+/// `parent` is the path of the element `tree` is built into, by child
+/// position. Unmanaged keys are prefixed with it: keyed nodes keep their
+/// identity among their siblings, not across parents.
+fn build(tree: &Tree, parent: &str, position: &mut usize, keys: &mut HashSet<String>) -> Html<Msg> {
+    if let Tree::Fragment(children) = tree {
+        // FLATTENED INTO THE PARENT, SO IT TAKES NO POSITION OF ITS OWN
+        return Html::new_fragment(children.iter().map(|x| build(x, parent, position, keys)).collect());
+    }
+    *position += 1;
+    match tree {
+        Tree::Element{tag, attributes, styles, events, children} => {
+            let mut node = Html::new_node(tag.to_string());
+            decorate(&mut node, attributes, styles);
+            for (event_name, msg) in events.iter().cloned() {
+                node.add_event_handler(event_name.to_owned(), Rc::new(move |_| msg));
+            }
+            let path = format!("{}/{}", parent, position);
+            let mut child_position = 0;
+            for child in children {
+                node.add_child(build(child, &path, &mut child_position, keys));
+            }
+            node
+        }
+        Tree::Unmanaged{tag, key, attributes, styles} => {
+            // KEYS ARE UNIQUE WITHIN A DOCUMENT; REPEATS BECOME PLAIN ELEMENTS
+            let key = format!("{}:{}", parent, key);
+            let mut node = if keys.insert(key.clone()) {
+                Html::new_unmanaged(tag.to_string(), key)
+            } else {
+                Html::new_node(tag.to_string())
+            };
+            decorate(&mut node, attributes, styles);
+            node
+        }
+        Tree::Fragment(_) => unreachable!(),
+        Tree::Text(value) => Html::Text{value: value.to_string()},
+        Tree::Comment(value) => Html::new_comment(value.to_string()),
+        Tree::Raw(value) => Html::new_raw(value.to_string()),
+    }
+}
+
+fn decorate(node: &mut Html<Msg>, attributes: &[Attr], styles: &[Decl]) {
+    for attribute in attributes {
+        node.add_attribute(match attribute {
+            Attr::Pair(key, value) => Attribute::Pair{key: key.to_string(), value: value.to_string()},
+            Attr::Toggle(key, value) => Attribute::Toggle{key: key.to_string(), value: *value},
+        });
+    }
+    for Decl(pseudo, property, value) in styles {
+        let style = Style::Style{property: property.to_string(), value: value.to_string()};
+        node.add_style(match pseudo {
+            Some(pseudo) => Style::PseudoClass(pseudo.to_string(), vec![style]),
+            None => style,
+        });
+    }
+}
+
+/// The tree under a root `div`, as `core::Process` mounts views.
+fn root(tree: &Tree) -> Html<Msg> {
+    let mut root = Html::new_node(String::from("div"));
+    root.add_child(build(tree, "", &mut 0, &mut HashSet::new()));
+    root
+}
+
+fn mount(dom: &MockDom, tree: &Tree) -> Html<Msg> {
+    let root = root(tree);
+    dom.mount(&root);
+    root
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// STRATEGIES
+///////////////////////////////////////////////////////////////////////////////
+
+// SMALL ALPHABETS, SO GENERATED TREES OFTEN LINE UP AND GET PATCHED IN PLACE
+const TAGS: &[&str] = &["div", "span", "svg", "g"];
+const KEYS: &[&str] = &["a", "b", "c"];
+
+fn attributes() -> impl Strategy<Value = Vec<Attr>> {
+    let attr = prop_oneof![
+        (select(vec!["class", "title", "xlink:href"]), select(vec!["x", "y"]))
+            .prop_map(|(key, value)| Attr::Pair(key, value)),
+        (select(vec!["hidden", "class"]), any::<bool>())
+            .prop_map(|(key, value)| Attr::Toggle(key, value)),
+    ];
+    vec(attr, 0..3)
+}
+
+fn styles() -> impl Strategy<Value = Vec<Decl>> {
+    let decl = (
+        prop::option::weighted(0.2, Just("hover")),
+        select(vec!["color", "margin_top"]),
+        select(vec!["red", "blue"]),
+    ).prop_map(|(pseudo, property, value)| Decl(pseudo, property, value));
+    vec(decl, 0..3)
+}
+
+fn events() -> impl Strategy<Value = Vec<(&'static str, Msg)>> {
+    vec((select(vec!["click", "input"]), 0..4u8), 0..3)
+}
+
+fn element(children: Vec<Tree>) -> impl Strategy<Value = Tree> {
+    (select(TAGS), attributes(), styles(), events()).prop_map(move |(tag, attributes, styles, events)| {
+        Tree::Element {
+            tag,
+            attributes,
+            styles,
+            events,
+            children: children.clone(),
+        }
+    })
+}
+
+fn leaf() -> impl Strategy<Value = Tree> {
+    prop_oneof![
+        select(vec!["a", "b", ""]).prop_map(Tree::Text),
+        select(vec!["x", "y"]).prop_map(Tree::Comment),
+        select(vec!["<b>x</b>", "<i>y</i>"]).prop_map(Tree::Raw),
+        (select(TAGS), select(KEYS), attributes(), styles()).prop_map(|(tag, key, attributes, styles)| {
+            Tree::Unmanaged {tag, key, attributes, styles}
+        }),
+        element(Vec::new()),
+    ]
+}
+
+/// Pairs of trees, mostly sharing their shape so that patches reach deep.
+fn tree_pair() -> impl Strategy<Value = (Tree, Tree)> {
+    (leaf(), leaf()).prop_recursive(4, 48, 4, |inner| prop_oneof![
+        // SAME TAG, CHILDREN PAIRED UP
+        (select(TAGS), element(Vec::new()), element(Vec::new()), vec(inner.clone(), 0..4))
+            .prop_map(|(tag, old, new, children)| {
+                let (c1, c2): (Vec<Tree>, Vec<Tree>) = children.into_iter().unzip();
+                (with_children(old, tag, c1), with_children(new, tag, c2))
+            }),
+        // CHILDREN ADDED OR REMOVED
+        (select(TAGS), element(Vec::new()), element(Vec::new()), vec(inner.clone(), 0..4), vec(inner.clone(), 0..4))
+            .prop_map(|(tag, old, new, c1, c2)| {
+                let c1 = c1.into_iter().map(|x| x.0).collect();
+                let c2 = c2.into_iter().map(|x| x.1).collect();
+                (with_children(old, tag, c1), with_children(new, tag, c2))
+            }),
+        // FRAGMENTS FLATTEN INTO THE PARENT
+        vec(inner.clone(), 0..3).prop_map(|children| {
+            let (c1, c2): (Vec<Tree>, Vec<Tree>) = children.into_iter().unzip();
+            (Tree::Fragment(c1), Tree::Fragment(c2))
+        }),
+        // UNRELATED SUBTREES
+        (inner.clone(), inner).prop_map(|((old, _), (_, new))| (old, new)),
+    ])
+}
+
+fn with_children(tree: Tree, tag: &'static str, children: Vec<Tree>) -> Tree {
+    match tree {
+        Tree::Element{attributes, styles, events, ..} => Tree::Element {
+            tag,
+            attributes,
+            styles,
+            events,
+            children,
+        },
+        other => other,
+    }
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// DOCUMENT SNAPSHOTS
+///////////////////////////////////////////////////////////////////////////////
+
+/// Everything observable about the mounted tree: structure, namespaces,
+/// attributes, style rules and what each listener dispatches. Ids differ
+/// between the patched and the fresh document, so they are left out.
+/// Empty text nodes are invisible and may or may not exist.
+fn snapshot(dom: &MockDom, root: &Html<Msg>) -> String {
+    let mount = dom.find_by_id(&root.id().expect("missing root id")).expect("root not mounted");
+    let mut out = String::new();
+    write_node(dom, root, mount, 0, &mut out);
+    out
+}
+
+fn write_node(dom: &MockDom, root: &Html<Msg>, node: MockId, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    let mock = dom.node(node);
+    match &mock.kind {
+        MockKind::Element{tag, namespace} => {
+            out.push_str(&format!("{}<{} ns={:?}", indent, tag, namespace));
+            for (key, value) in mock.attributes.iter().filter(|(key, _)| *key != "id") {
+                out.push_str(&format!(" {}={:?}", key, value));
+            }
+            out.push_str(">\n");
+            if let Some(id) = mock.attributes.get("id") {
+                let selector = format!("#{}", id);
+                for rule in dom.rules().iter().filter(|x| Style::is_rule_for(x, id)) {
+                    out.push_str(&format!("{}  @ {}\n", indent, rule.replacen(&selector, "#", 1)));
+                }
+            }
+            for event_name in dom.listeners(node) {
+                dom.dispatch(node, &event_name, JsValue::UNDEFINED);
+                out.push_str(&format!("{}  on {} => {:?}\n", indent, event_name, root.tick()));
+            }
+            for child in mock.children {
+                write_node(dom, root, child, depth + 1, out);
+            }
+        }
+        MockKind::Text(value) if value.is_empty() => (),
+        MockKind::Text(value) => out.push_str(&format!("{}text {:?}\n", indent, value)),
+        MockKind::Comment(value) => out.push_str(&format!("{}comment {:?}\n", indent, value)),
+        MockKind::Raw(value) => out.push_str(&format!("{}raw {:?}\n", indent, value)),
+        MockKind::Fragment => panic!("fragment left in the document"),
+    }
+}
+
+/// Patches a document showing `old` into `new`, then checks it against a
+/// document that mounted `new` directly.
+fn check_patch(dom: &MockDom, active: &mut Html<Msg>, new: &Tree) -> Result<(), TestCaseError> {
+    active.sync(&mut root(new), String::from("body"), dom);
+    let fresh_dom = MockDom::new();
+    let fresh = mount(&fresh_dom, new);
+    prop_assert_eq!(snapshot(dom, active), snapshot(&fresh_dom, &fresh));
+    prop_assert_eq!(dom.rules().len(), fresh_dom.rules().len(), "stale style rules");
+    prop_assert_eq!(active.to_html_string(), fresh.to_html_string(), "vnode out of sync");
+    Ok(())
+}
+
+
+///////////////////////////////////////////////////////////////////////////////
+// PROPERTIES
+///////////////////////////////////////////////////////////////////////////////
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn patch_matches_fresh_render((old, new) in tree_pair()) {
+        let dom = MockDom::new();
+        let mut active = mount(&dom, &old);
+        check_patch(&dom, &mut active, &new)?;
+        // AND BACK, FROM A TREE THAT WAS ITSELF PATCHED
+        check_patch(&dom, &mut active, &old)?;
+    }
+
+    #[test]
+    fn patch_to_same_tree_is_a_no_op((tree, _) in tree_pair()) {
+        let dom = MockDom::new();
+        let mut active = mount(&dom, &tree);
+        dom.take_ops();
+        active.sync(&mut root(&tree), String::from("body"), &dom);
+        prop_assert_eq!(dom.take_ops(), Vec::new());
+    }
+}
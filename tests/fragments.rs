@@ -0,0 +1,57 @@
+//! Fragments, comments and placeholders: fragments flatten into their
+//! parent, and comments hold the place of branches the view leaves out.
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use view_prototype::core::{Component, Process};
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+
+type Msg = u8;
+
+#[test]
+fn fragments_flatten_into_their_parent() {
+    let dom = MockDom::new();
+    let root: Html<Msg> = view!(div|
+        p(),
+        {Html::new_fragment(vec![Html::new_node(String::from("span")), Html::Text{value: String::from("x")}])},
+        b()
+    );
+    dom.mount(&root);
+    assert_eq!(dom.bare_body_html(), "<div><p></p><span></span>x<b></b></div>");
+    let div = dom.find_by_tag("div").unwrap();
+    assert_eq!(dom.node(div).children.len(), 4);
+}
+
+#[test]
+fn views_may_return_fragments_and_comments() {
+    let dom = Rc::new(MockDom::new());
+    let view = |count: &u8| match count {
+        0 => Html::new_comment(String::from("empty")),
+        n => Html::new_fragment((0..*n).map(|x| view!(li| text (x.to_string()))).collect()),
+    };
+    let process = Process::with_backend(Component::new(0, |count: &mut u8, msg: Msg| *count = msg, view), dom.clone());
+    assert_eq!(dom.bare_body_html(), "<div><!--empty--></div>");
+    process.send(2);
+    process.tick();
+    assert_eq!(dom.bare_body_html(), "<div><li>0</li><li>1</li></div>");
+    process.send(0);
+    process.tick();
+    assert_eq!(dom.bare_body_html(), "<div><!--empty--></div>");
+}
+
+#[test]
+fn placeholders_stand_in_for_hidden_branches() {
+    let dom = MockDom::new();
+    let view = |shown: bool| -> Html<Msg> {
+        let note = if shown {view!(p| text "note")} else {Html::placeholder()};
+        view!(div| {note}, input())
+    };
+    let mut active = view(false);
+    dom.mount(&active);
+    assert_eq!(dom.bare_body_html(), "<div><!----><input></input></div>");
+    active.sync(&mut view(true), String::from("body"), &dom);
+    assert_eq!(dom.bare_body_html(), "<div><p>note</p><input></input></div>");
+    active.sync(&mut view(false), String::from("body"), &dom);
+    assert_eq!(dom.bare_body_html(), "<div><!----><input></input></div>");
+}
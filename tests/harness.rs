@@ -0,0 +1,55 @@
+//! The native test harness, checked against a process mounted on `MockDom`.
+#![cfg(feature = "ssr")]
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use view_prototype::core::{Component, Process};
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+use view_prototype::platform::JsValue;
+use view_prototype::testing::{Find, Harness};
+
+type Msg = u8;
+
+fn tally() -> Component<u32, Msg> {
+    Component::new(
+        0,
+        |model: &mut u32, msg: Msg| *model += u32::from(msg),
+        |model: &u32| view!(div|
+            p(text (model.to_string())),
+            button(.click(|_| 2), text "add")
+        ),
+    )
+}
+
+#[test]
+fn harness_agrees_with_a_mounted_process() {
+    let mut harness = Harness::new(tally());
+    let dom = Rc::new(MockDom::new());
+    let process = Process::with_backend(tally(), dom.clone());
+    for _ in 0..2 {
+        harness.dispatch(Find::text("add"), "click");
+        assert!(dom.dispatch(dom.find_by_tag("button").unwrap(), "click", JsValue::UNDEFINED));
+        process.tick();
+    }
+    assert_eq!(harness.model(), 4);
+    assert_eq!(process.model(), harness.model());
+    assert_eq!(dom.bare_body_html(), format!("<div>{}</div>", harness.html()));
+}
+
+// THE BROWSER BUILD'S `JsValue` ONLY EXISTS INSIDE WASM
+#[cfg(not(feature = "dom"))]
+#[test]
+fn handlers_read_the_dispatched_value() {
+    let mut harness = Harness::new(Component::new(
+        String::new(),
+        |model: &mut String, name: String| *model = name,
+        |model: &String| view!(div|
+            input(.input(|event: JsValue| event.as_string().unwrap_or_default())),
+            p(text (model.clone()))
+        ),
+    ));
+    harness.dispatch_with(Find::tag("input"), "input", JsValue::from_str("ada"));
+    assert_eq!(harness.model(), "ada");
+    assert_eq!(harness.html(), "<div><input></input><p>ada</p></div>");
+}
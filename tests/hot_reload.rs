@@ -0,0 +1,79 @@
+//! Hot-reload snapshots: restored by a build with the same model schema,
+//! discarded by any other.
+
+use serde::{Serialize, Deserialize};
+use view_prototype::debugger::Session;
+use view_prototype::hot_reload::{fingerprint, HotReload};
+use view_prototype::persist::{MemoryStorage, Storage};
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+struct Model {
+    total: u32,
+}
+
+/// `Model` after a rebuild that added a field.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+struct Rebuilt {
+    total: u32,
+    label: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+enum Msg {
+    Add(u32),
+}
+
+fn hot_reload(store: &MemoryStorage) -> HotReload<Model, Msg> {
+    HotReload::new(store.clone(), "app", &Model {total: 0})
+}
+
+#[test]
+fn fingerprints_follow_the_schema_not_the_values() {
+    assert_eq!(fingerprint(&Model {total: 0}), fingerprint(&Model {total: 9}));
+    assert_ne!(
+        fingerprint(&Model {total: 0}),
+        fingerprint(&Rebuilt {total: 0, label: String::new()}),
+    );
+}
+
+#[test]
+fn restores_the_model_saved_by_the_same_build() {
+    let store = MemoryStorage::new();
+    hot_reload(&store).save(&Model {total: 3}, None);
+    assert_eq!(hot_reload(&store).restore(), Some((Model {total: 3}, None)));
+    // TAKEN, SO A LATER RELOAD STARTS FRESH
+    assert_eq!(hot_reload(&store).restore(), None);
+}
+
+#[test]
+fn restores_the_debug_session_when_kept() {
+    let store = MemoryStorage::new();
+    let mut session = Session::new(Model {total: 0});
+    session.record(Msg::Add(3), Model {total: 3});
+    hot_reload(&store).save(&Model {total: 3}, Some(&session));
+    assert_eq!(hot_reload(&store).restore(), Some((Model {total: 3}, None)));
+    let keeping = hot_reload(&store).keep_messages(true);
+    keeping.save(&Model {total: 3}, Some(&session));
+    assert_eq!(keeping.restore(), Some((Model {total: 3}, Some(session))));
+}
+
+#[test]
+fn another_schema_discards_the_snapshot() {
+    let store = MemoryStorage::new();
+    hot_reload(&store).save(&Model {total: 3}, None);
+    let rebuilt: HotReload<Rebuilt, Msg> = HotReload::new(
+        store.clone(),
+        "app",
+        &Rebuilt {total: 0, label: String::new()},
+    );
+    assert_eq!(rebuilt.restore(), None);
+    assert_eq!(store.get("app/hot-reload"), None);
+}
+
+#[test]
+fn corrupt_snapshots_are_discarded() {
+    let store = MemoryStorage::new();
+    store.set("app/hot-reload", "not json");
+    assert_eq!(hot_reload(&store).restore(), None);
+    assert_eq!(store.get("app/hot-reload"), None);
+}
@@ -0,0 +1,100 @@
+//! The JSX-like `html!` against the same views built with `view!`.
+#![cfg(feature = "ssr")]
+
+#[macro_use] extern crate view_prototype;
+use view_prototype::html;
+use view_prototype::css::CssValue;
+use view_prototype::core::Component;
+use view_prototype::html::*;
+use view_prototype::platform::JsValue;
+use view_prototype::testing::{Find, Harness};
+
+#[derive(Debug, PartialEq, Clone, Hash)]
+enum Msg {
+    Save,
+    Edit(String),
+}
+
+fn assert_same(left: &Html<Msg>, right: &Html<Msg>) {
+    assert_eq!(left.to_pretty_string(), right.to_pretty_string());
+    assert_eq!(left.to_html_string(), right.to_html_string());
+}
+
+#[test]
+fn expands_to_the_same_view_as_the_macro() {
+    let name = "Jerry";
+    let built: Html<Msg> = html! {
+        <section class="card" style="display: flex; background-color: #eee"
+            style:color={"red"} style:hover:color="blue" style:hover:font-weight="bold">
+            <h1 title={format!("Tom & {}", name)}>"Tom & " {Html::Text{value: name.to_owned()}}</h1>
+            <input type="text" disabled=true aria-label="name" @value={"draft"} />
+            <br/>
+            <svg viewBox="0 0 8 8"><circle r="4"/></svg>
+            <my-widget data-count={3}></my-widget>
+        </section>
+    };
+    let title = format!("Tom & {}", name);
+    let expected: Html<Msg> = view!(section|
+        class = "card",
+        display: "flex",
+        background_color: "#eee",
+        color: "red",
+        :hover(color: "blue", font_weight: "bold"),
+        h1(title = title, text "Tom & ", text name),
+        input(type = "text", disabled = true, "aria-label" = "name", @value = "draft"),
+        br(),
+        svg(viewBox = "0 0 8 8", circle(r = "4")),
+        my_widget("data-count" = "3")
+    );
+    // `view!` CAN'T NAME HYPHENATED TAGS
+    let expected = expected.to_pretty_string().replace("my_widget", "my-widget");
+    assert_eq!(built.to_pretty_string(), expected);
+}
+
+#[test]
+fn several_top_level_nodes_make_a_fragment() {
+    let built: Html<Msg> = html!(<p>"a"</p> "text" <p>"b"</p>);
+    let expected = Html::new_fragment(vec![
+        view!(p| text "a"),
+        Html::Text{value: String::from("text")},
+        view!(p| text "b"),
+    ]);
+    assert_same(&built, &expected);
+    match built {
+        Html::Fragment{..} => (),
+        other => panic!("expected a fragment, got {:?}", other),
+    }
+}
+
+#[test]
+fn handlers_and_hooks() {
+    let node_ref = NodeRef::new();
+    let built: Html<Msg> = html! {
+        <form ref={node_ref.clone()} on_mount={|_| None}>
+            <input on:input={|_| Msg::Edit(String::from("typed"))} />
+            <button on:click=[Msg::Save]>"Save"</button>
+        </form>
+    };
+    let save = &built.query("button").unwrap().events().unwrap()["click"];
+    assert_eq!(save.message, Some(Msg::Save));
+    let edit = &built.query("input").unwrap().events().unwrap()["input"];
+    assert_eq!(edit.eval(JsValue::UNDEFINED), Some(Msg::Edit(String::from("typed"))));
+}
+
+fn counter(count: &u32) -> Html<Msg> {
+    html! {
+        <div class="counter">
+            <span>{Html::Text{value: count.to_string()}}</span>
+            <button on:click=[Msg::Save]>"Save"</button>
+        </div>
+    }
+}
+
+#[test]
+fn harness_dispatches_to_macro_views() {
+    let spec = Component::new(0u32, |count: &mut u32, _: Msg| *count += 1, counter);
+    let mut harness = Harness::new(spec);
+    harness.dispatch(Find::selector("button:text(Save)"), "click");
+    assert_eq!(harness.model(), 1);
+    assert!(harness.html().contains(">1</span>"), "{}", harness.html());
+}
@@ -0,0 +1,8 @@
+//! `html!` errors point at the offending token; see the `.stderr` files.
+#![cfg(feature = "ssr")]
+
+#[test]
+fn html_macro_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
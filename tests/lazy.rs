@@ -0,0 +1,55 @@
+//! Lazy subtrees: compared by closure identity, and rebuilt only when the
+//! inputs they hash change.
+
+#[macro_use] extern crate view_prototype;
+use std::cell::Cell;
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+
+type Msg = u8;
+
+thread_local! {
+    static ROWS_BUILT: Cell<usize> = const { Cell::new(0) };
+}
+
+fn row(title: &&'static str) -> Html<Msg> {
+    ROWS_BUILT.with(|x| x.set(x.get() + 1));
+    view!(li| text *title)
+}
+
+fn list(titles: &[&'static str]) -> Html<Msg> {
+    let rows = titles.iter().map(|x| lazy(*x, row)).collect();
+    view!(div| ul({Html::new_fragment(rows)}))
+}
+
+#[test]
+fn lazy_views_are_equal_only_when_they_share_a_closure() {
+    let milk = lazy("milk", row);
+    assert_eq!(milk.clone(), milk);
+    // SAME INPUTS, SO THE SAME HASH, BUT A CLOSURE OF ITS OWN
+    let again = lazy("milk", row);
+    match (&milk, &again) {
+        (Html::Lazy{hash: h1, view: v1, ..}, Html::Lazy{hash: h2, view: v2, ..}) => {
+            assert_eq!(h1, h2);
+            assert_ne!(v1, v2);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_ne!(milk, again);
+}
+
+#[test]
+fn lazy_nodes_rebuild_only_when_their_inputs_change() {
+    let dom = MockDom::new();
+    let mut active = list(&["milk", "eggs"]);
+    active.force_all();
+    dom.mount(&active);
+    ROWS_BUILT.with(|x| x.set(0));
+    dom.take_ops();
+    active.sync(&mut list(&["milk", "eggs"]), String::from("body"), &dom);
+    assert_eq!(ROWS_BUILT.with(|x| x.get()), 0);
+    assert_eq!(dom.take_ops(), Vec::new());
+    active.sync(&mut list(&["milk", "bread"]), String::from("body"), &dom);
+    assert_eq!(ROWS_BUILT.with(|x| x.get()), 1);
+    assert_eq!(dom.bare_body_html(), "<div><ul><li>milk</li><li>bread</li></ul></div>");
+}
@@ -0,0 +1,51 @@
+//! Lifecycle hooks: compared by closure identity, so an unchanged view
+//! patches none of them.
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+use view_prototype::platform::Element;
+use view_prototype::sync::Patch;
+
+type Msg = u8;
+
+type HookFn = Rc<Fn(&Element)->Option<Msg>>;
+
+fn with_hook(hook: &HookFn) -> Html<Msg> {
+    let mut canvas = Html::new_node(String::from("canvas"));
+    canvas.set_on_mount(hook.clone());
+    view!(div| {canvas})
+}
+
+fn lifecycle_patches(patches: &[Patch<Msg>]) -> usize {
+    patches.iter().filter(|x| matches!(x, Patch::SetLifecycle{..})).count()
+}
+
+#[test]
+fn hooks_are_equal_only_when_they_share_a_closure() {
+    let hook: HookFn = Rc::new(|_| None);
+    let other: HookFn = Rc::new(|_| Some(1));
+    assert_eq!(Hook(hook.clone()), Hook(hook.clone()));
+    assert_ne!(Hook(hook.clone()), Hook(other.clone()));
+    let lifecycle = |hook| with_hook(hook).query("canvas").and_then(|x| x.lifecycle());
+    assert_eq!(lifecycle(&hook), lifecycle(&hook));
+    assert_ne!(lifecycle(&hook), lifecycle(&other));
+}
+
+#[test]
+fn lifecycle_is_patched_only_when_its_hooks_change() {
+    let dom = MockDom::new();
+    let hook: HookFn = Rc::new(|_| None);
+    let other: HookFn = Rc::new(|_| Some(1));
+    let mut active = with_hook(&hook);
+    dom.mount(&active);
+    assert_eq!(active.patches(&mut with_hook(&hook), String::from("body")), Vec::new());
+    let patches = active.patches(&mut with_hook(&other), String::from("body"));
+    assert_eq!(lifecycle_patches(&patches), 1);
+    active.apply_patches(&patches, &dom);
+    let canvas = active.query("canvas").unwrap();
+    assert_eq!(canvas.lifecycle().unwrap().on_mount, Some(Hook(other.clone())));
+    assert_eq!(lifecycle_patches(&active.patches(&mut with_hook(&other), String::from("body"))), 0);
+}
+
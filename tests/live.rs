@@ -0,0 +1,222 @@
+//! A `LiveServer` and `LiveClient` talking over an in-process channel.
+#![cfg(feature = "ssr")]
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use serde::{Serialize, Deserialize};
+use view_prototype::backend::Backend;
+use view_prototype::css::CssValue;
+use view_prototype::core::Component;
+use view_prototype::html::*;
+use view_prototype::live::{self, Transport, LiveServer, LiveClient, ServerFrame, ClientFrame};
+use view_prototype::mock_dom::MockDom;
+use view_prototype::platform::JsValue;
+
+#[derive(Debug, PartialEq, Clone, Hash)]
+struct Model {
+    count: u32,
+    draft: String,
+    items: Vec<String>,
+    name: String,
+    /// Not shown in the view.
+    touches: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Hash, Serialize, Deserialize)]
+enum Msg {
+    Increment,
+    Edit(String),
+    Add,
+    Rename(String),
+    Touch,
+}
+
+fn update(model: &mut Model, msg: Msg) {
+    match msg {
+        Msg::Increment => model.count += 1,
+        Msg::Edit(value) => model.draft = value,
+        Msg::Add => model.items.push(std::mem::take(&mut model.draft)),
+        Msg::Rename(name) => model.name = name,
+        Msg::Touch => model.touches += 1,
+    }
+}
+
+fn item(title: &String) -> Html<Msg> {
+    view!(li| color: "gray", text title)
+}
+
+fn view(model: &Model) -> Html<Msg> {
+    let items: Vec<Html<Msg>> = model.items.iter().map(|x| lazy(x.clone(), item)).collect();
+    view!(
+        p(text format!("count {}", model.count)),
+        button(class = "increment", .click[Msg::Increment], text "+"),
+        input(@value = model.draft.as_str(), .input(|_| Msg::Edit(String::from("milk")))),
+        button(class = "add", .click[Msg::Add], text "Add"),
+        button(class = "touch", .click[Msg::Touch], text "Touch"),
+        ul({Html::new_fragment(items)}),
+        input(class = "name", .input(|event: JsValue| Msg::Rename(event.as_string().unwrap_or_default()))),
+        h1(text format!("hello {}", model.name))
+    )
+}
+
+fn component() -> Component<Model, Msg> {
+    Component::new(Model {count: 0, draft: String::new(), items: Vec::new(), name: String::new(), touches: 0}, update, view)
+}
+
+fn connect() -> (LiveServer<Model, Msg, live::Channel>, LiveClient<Msg, live::Channel>, Rc<MockDom>) {
+    let (server_end, client_end) = live::channel();
+    let server = LiveServer::new(component(), server_end);
+    let dom = Rc::new(MockDom::new());
+    let mut client = LiveClient::with_backend(client_end, dom.clone());
+    client.tick();
+    (server, client, dom)
+}
+
+/// Fires `event_name` on the client's element matching `selector`.
+fn fire(client: &LiveClient<Msg, live::Channel>, dom: &MockDom, selector: &str, event_name: &str) {
+    fire_with(client, dom, selector, event_name, JsValue::UNDEFINED);
+}
+
+fn fire_with(client: &LiveClient<Msg, live::Channel>, dom: &MockDom, selector: &str, event_name: &str, value: JsValue) {
+    let id = client.view()
+        .and_then(|view| view.query(selector))
+        .and_then(|node| node.id())
+        .expect("no such element");
+    let node = dom.find_by_id(&id).expect("element not mounted");
+    assert!(dom.dispatch(node, event_name, value));
+}
+
+/// One round trip: events to the server, patches back.
+fn round_trip(server: &LiveServer<Model, Msg, live::Channel>, client: &mut LiveClient<Msg, live::Channel>) {
+    client.tick();
+    server.tick();
+    client.tick();
+}
+
+/// The client view matches the server's as sent: lazy views and closure
+/// handlers don't survive the trip, so the views themselves differ.
+fn assert_mirrors(server: &LiveServer<Model, Msg, live::Channel>, client: &LiveClient<Msg, live::Channel>) {
+    let view = client.view().expect("client has no view");
+    assert_eq!(live::encode(view), live::encode(&server.process().view()));
+}
+
+#[test]
+fn client_mounts_the_server_view() {
+    let (server, client, dom) = connect();
+    assert_mirrors(&server, &client);
+    assert!(dom.body_html().contains(">count 0</p>"), "{}", dom.body_html());
+}
+
+#[test]
+fn message_handlers_send_their_message() {
+    let (server, mut client, dom) = connect();
+    fire(&client, &dom, "button.increment", "click");
+    fire(&client, &dom, "button.increment", "click");
+    round_trip(&server, &mut client);
+    assert_eq!(server.process().model().count, 2);
+    assert!(dom.body_html().contains(">count 2</p>"), "{}", dom.body_html());
+    assert_mirrors(&server, &client);
+}
+
+#[test]
+fn closure_handlers_run_on_the_server() {
+    let (server, mut client, dom) = connect();
+    fire(&client, &dom, "input", "input");
+    round_trip(&server, &mut client);
+    assert_eq!(server.process().model().draft, "milk");
+    fire(&client, &dom, "button.add", "click");
+    round_trip(&server, &mut client);
+    assert_eq!(server.process().model().items, vec![String::from("milk")]);
+    assert!(dom.body_html().contains("<li"), "{}", dom.body_html());
+    assert_mirrors(&server, &client);
+    // THE NEW ITEM KEEPS ITS IDS, SO LATER PATCHES STILL FIND IT
+    fire(&client, &dom, "button.increment", "click");
+    round_trip(&server, &mut client);
+    assert!(dom.body_html().contains(">count 1</p>"), "{}", dom.body_html());
+    assert_mirrors(&server, &client);
+}
+
+// THE BROWSER BUILD'S `JsValue` ONLY EXISTS INSIDE WASM
+#[cfg(not(feature = "dom"))]
+#[test]
+fn handlers_read_the_value_the_client_sent() {
+    let (server, mut client, dom) = connect();
+    fire_with(&client, &dom, "input.name", "input", JsValue::from_str("ada"));
+    round_trip(&server, &mut client);
+    assert_eq!(server.process().model().name, "ada");
+    assert!(dom.body_html().contains(">hello ada</h1>"), "{}", dom.body_html());
+    assert_mirrors(&server, &client);
+}
+
+#[test]
+fn clients_missing_an_element_resync() {
+    let (server, mut client, dom) = connect();
+    // A SCRIPT REMOVES THE COUNTER BEHIND THE CLIENT'S BACK
+    let counter = client.view().and_then(|view| view.query("p")).and_then(|node| node.id()).unwrap();
+    dom.remove_node(&dom.get_element_by_id(&counter).unwrap());
+    fire(&client, &dom, "button.increment", "click");
+    round_trip(&server, &mut client);
+    assert!(!dom.body_html().contains("count"), "{}", dom.body_html());
+    // THE RESYNC REQUEST, ANSWERED WITH THE WHOLE VIEW
+    round_trip(&server, &mut client);
+    assert!(dom.body_html().contains(">count 1</p>"), "{}", dom.body_html());
+    assert_mirrors(&server, &client);
+    fire(&client, &dom, "button.increment", "click");
+    round_trip(&server, &mut client);
+    assert!(dom.body_html().contains(">count 2</p>"), "{}", dom.body_html());
+}
+
+/// The client end of a server, after the mount frame.
+fn raw_client() -> (LiveServer<Model, Msg, live::Channel>, live::Channel) {
+    let (server_end, client_end) = live::channel();
+    let server = LiveServer::new(component(), server_end);
+    let mount: ServerFrame<Msg> = client_end.receive().and_then(|x| live::decode(&x)).unwrap();
+    assert_eq!(mount, ServerFrame::Mount(server.process().view()));
+    (server, client_end)
+}
+
+fn event(server: &LiveServer<Model, Msg, live::Channel>, selector: &str, event_name: &str) -> Vec<u8> {
+    let id = server.process().view().query(selector).and_then(|node| node.id()).unwrap();
+    live::encode(&ClientFrame::Event {id, event_name: String::from(event_name), value: None})
+}
+
+#[test]
+fn patches_are_streamed_as_binary_frames() {
+    let (server, client_end) = raw_client();
+    client_end.send(event(&server, "button.increment", "click"));
+    server.tick();
+    let frame = client_end.receive().unwrap();
+    match live::decode::<ServerFrame<Msg>>(&frame) {
+        Some(ServerFrame::Patch(patches)) => assert!(!patches.is_empty()),
+        frame => panic!("unexpected {:?}", frame),
+    }
+    // NOTHING CHANGED, NOTHING SENT
+    server.tick();
+    assert_eq!(client_end.receive(), None);
+}
+
+#[test]
+fn unchanged_views_send_no_patch_frame() {
+    let (server, client_end) = raw_client();
+    client_end.send(event(&server, "button.touch", "click"));
+    server.tick();
+    assert_eq!(server.process().model().touches, 1);
+    assert_eq!(client_end.receive(), None);
+}
+
+#[test]
+fn malformed_and_forged_frames_are_dropped() {
+    let (server, client_end) = raw_client();
+    client_end.send(vec![255, 1, 2]);
+    client_end.send(live::encode(&ClientFrame::Event {
+        id: String::from("missing"),
+        event_name: String::from("click"),
+        value: None,
+    }));
+    // NO HANDLER FOR THE EVENT, OR ON THE ELEMENT
+    client_end.send(event(&server, "button.increment", "dblclick"));
+    client_end.send(event(&server, "p", "click"));
+    server.tick();
+    assert_eq!(server.process().model().count, 0);
+    assert_eq!(client_end.receive(), None);
+}
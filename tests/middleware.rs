@@ -0,0 +1,94 @@
+//! Middleware stages on `Process`: order, extra messages and swallowed ones.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use view_prototype::core::{Component, Process};
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+
+#[derive(Debug, PartialEq, Clone, Hash)]
+enum Msg {
+    Add(u32),
+    Log(String),
+}
+
+#[derive(Debug, PartialEq, Clone, Hash, Default)]
+struct Model {
+    total: u32,
+    log: Vec<String>,
+}
+
+fn update(model: &mut Model, msg: Msg) {
+    match msg {
+        Msg::Add(n) => model.total += n,
+        Msg::Log(line) => model.log.push(line),
+    }
+}
+
+fn view(model: &Model) -> Html<Msg> {
+    let mut node = Html::new_node(String::from("p"));
+    node.add_child(Html::Text{value: model.total.to_string()});
+    node
+}
+
+fn process() -> Process<Model, Msg> {
+    Process::with_backend(Component::new(Model::default(), update, view), Rc::new(MockDom::new()))
+}
+
+#[test]
+fn stages_run_in_registration_order() {
+    let process = process();
+    let seen: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+    for name in ["first", "second"].iter() {
+        let seen = seen.clone();
+        process.add_middleware(move |_, msg, next| {
+            seen.borrow_mut().push(format!("{} {:?}", name, msg));
+            // EACH STAGE SEES WHAT THE ONE BEFORE PASSED ON
+            match msg {
+                Msg::Add(n) => next(Msg::Add(n * 10)),
+                msg => next(msg.clone()),
+            }
+        });
+    }
+    process.send(Msg::Add(1));
+    process.tick();
+    assert_eq!(*seen.borrow(), vec!["first Add(1)", "second Add(10)"]);
+    assert_eq!(process.model().total, 100);
+}
+
+#[test]
+fn stages_can_send_extra_messages() {
+    let process = process();
+    process.add_middleware(|model, msg, next| {
+        next(msg.clone());
+        if let Msg::Add(n) = msg {
+            next(Msg::Log(format!("{} + {}", model.total, n)));
+        }
+    });
+    process.send(Msg::Add(2));
+    process.send(Msg::Add(3));
+    process.tick();
+    let model = process.model();
+    assert_eq!(model.total, 5);
+    // STAGES SEE THE MODEL AS IT IS BEFORE EACH MESSAGE
+    assert_eq!(model.log, vec!["0 + 2", "2 + 3"]);
+}
+
+#[test]
+fn stages_can_swallow_messages() {
+    let process = process();
+    process.add_middleware(|_, msg, next| {
+        if *msg != Msg::Add(0) {
+            next(msg.clone());
+        }
+    });
+    process.start_debugger();
+    process.send(Msg::Add(0));
+    process.send(Msg::Add(4));
+    process.tick();
+    assert_eq!(process.model().total, 4);
+    // ONLY WHAT REACHES `update` IS RECORDED
+    let session = process.debug_session().unwrap();
+    let recorded: Vec<Msg> = session.entries.into_iter().map(|x| x.msg).collect();
+    assert_eq!(recorded, vec![Msg::Add(4)]);
+}
@@ -0,0 +1,82 @@
+//! The `Backend` implementations: what `MockDom` records, and the same
+//! process driven on it and on `HeadlessBackend`.
+#![cfg(feature = "ssr")]
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use view_prototype::backend::{Backend, HeadlessBackend};
+use view_prototype::core::{Component, Process};
+use view_prototype::css::CssValue;
+use view_prototype::html::*;
+use view_prototype::mock_dom::{MockDom, Op};
+use view_prototype::platform::JsValue;
+
+type Msg = u8;
+
+fn button(listening: bool) -> Html<Msg> {
+    let button = if listening {
+        view!(button| title = "go", color: "red", .click(|_| 1), text "go")
+    } else {
+        view!(button| text "go")
+    };
+    view!(div| {button})
+}
+
+fn tally() -> Component<u32, Msg> {
+    Component::new(
+        0,
+        |model: &mut u32, msg: Msg| *model += u32::from(msg),
+        |model: &u32| view!(div|
+            p(text (model.to_string())),
+            button(.click(|_| 2), text "add")
+        ),
+    )
+}
+
+#[test]
+fn records_every_operation() {
+    let dom = MockDom::new();
+    let mut active = button(true);
+    dom.mount(&active);
+    let ops = dom.take_ops();
+    let node = dom.find_by_tag("button").unwrap();
+    assert!(ops.contains(&Op::CreateElement{node, tag: String::from("button"), namespace: None}));
+    assert!(ops.contains(&Op::SetAttribute{node, key: String::from("title"), value: String::from("go")}));
+    assert!(ops.contains(&Op::AddListener{node, event_name: String::from("click")}));
+    assert!(ops.iter().any(|op| match op {
+        Op::InsertRule(rule) => rule.contains("color: red"),
+        _ => false,
+    }), "{:?}", ops);
+    active.sync(&mut button(false), String::from("body"), &dom);
+    let ops = dom.take_ops();
+    assert!(ops.contains(&Op::RemoveAttribute{node, key: String::from("title")}), "{:?}", ops);
+    assert!(ops.contains(&Op::RemoveListener{node, event_name: String::from("click")}), "{:?}", ops);
+    assert!(ops.iter().any(|op| matches!(op, Op::DeleteRules(_))), "{:?}", ops);
+    assert!(dom.rules().iter().all(|rule| !rule.contains("color")), "{:?}", dom.rules());
+}
+
+#[test]
+fn backends_drive_the_same_process() {
+    let dom = Rc::new(MockDom::new());
+    let mounted = Process::with_backend(tally(), dom.clone());
+    let headless = Process::with_backend(tally(), Rc::new(HeadlessBackend::new()));
+    for process in [&mounted, &headless].iter() {
+        let button = process.view().find_all(|node| node.tag() == Some("button"))[0].id().unwrap();
+        assert!(process.dispatch(&button, "click", JsValue::UNDEFINED));
+        process.tick();
+    }
+    assert_eq!(headless.model(), 2);
+    assert_eq!(mounted.model(), headless.model());
+    assert_eq!(mounted.view().to_html_string(), headless.view().to_html_string());
+    assert!(dom.bare_body_html().contains("<p>2</p>"), "{}", dom.bare_body_html());
+}
+
+#[test]
+fn headless_ids_resolve_only_once_set() {
+    let backend = HeadlessBackend::new();
+    assert!(backend.get_element_by_id("a").is_none());
+    let node = backend.create_element("div", None);
+    backend.set_attribute(&node, "id", "a", None);
+    assert!(backend.get_element_by_id("a").is_some());
+    assert!(backend.get_element_by_id("b").is_none());
+}
@@ -0,0 +1,58 @@
+//! SVG elements: created in the SVG namespace, whether mounted, patched in
+//! or built late by a lazy node.
+
+#[macro_use] extern crate view_prototype;
+use view_prototype::html::*;
+use view_prototype::mock_dom::{MockDom, MockKind};
+
+type Msg = u8;
+
+fn namespace_of(dom: &MockDom, tag: &str) -> Option<String> {
+    match dom.node(dom.find_by_tag(tag).expect("missing element")).kind {
+        MockKind::Element{namespace, ..} => namespace,
+        kind => panic!("not an element: {:?}", kind),
+    }
+}
+
+#[test]
+fn svg_children_are_created_in_the_svg_namespace() {
+    let dom = MockDom::new();
+    let root: Html<Msg> = view!(div| svg(circle(), foreignObject(p())));
+    dom.mount(&root);
+    let svg = Some(SVG_NAMESPACE.to_owned());
+    assert_eq!(namespace_of(&dom, "svg"), svg);
+    assert_eq!(namespace_of(&dom, "circle"), svg);
+    assert_eq!(namespace_of(&dom, "foreignObject"), svg);
+    assert_eq!(namespace_of(&dom, "p"), None);
+    assert_eq!(namespace_of(&dom, "div"), None);
+}
+
+#[test]
+fn children_patched_into_svg_take_its_namespace() {
+    let dom = MockDom::new();
+    let view = |shapes: &[&str]| -> Html<Msg> {
+        let shapes = shapes.iter().map(|x| Html::new_node(x.to_string())).collect();
+        view!(div| svg({Html::new_fragment(shapes)}))
+    };
+    let mut active = view(&["circle"]);
+    dom.mount(&active);
+    active.sync(&mut view(&["rect", "circle"]), String::from("body"), &dom);
+    assert_eq!(namespace_of(&dom, "rect"), Some(SVG_NAMESPACE.to_owned()));
+    assert_eq!(namespace_of(&dom, "circle"), Some(SVG_NAMESPACE.to_owned()));
+}
+
+#[test]
+fn lazy_nodes_under_svg_take_its_namespace() {
+    fn shape(_: &u8) -> Html<Msg> {
+        Html::new_node(String::from("g"))
+    }
+    let dom = MockDom::new();
+    let view = |n: u8| -> Html<Msg> {view!(div| svg({lazy(n, shape)}))};
+    let mut active = view(0);
+    active.force_all();
+    dom.mount(&active);
+    assert_eq!(namespace_of(&dom, "g"), Some(SVG_NAMESPACE.to_owned()));
+    // BUILT LATE, BY THE PATCH
+    active.sync(&mut view(1), String::from("body"), &dom);
+    assert_eq!(namespace_of(&dom, "g"), Some(SVG_NAMESPACE.to_owned()));
+}
@@ -0,0 +1,45 @@
+//! Node refs: patched onto the vnode only when the view hands over another
+//! ref, and cleared once it hands over none.
+
+#[macro_use] extern crate view_prototype;
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+use view_prototype::sync::Patch;
+
+type Msg = u8;
+
+fn with_ref(node_ref: Option<&NodeRef>) -> Html<Msg> {
+    match node_ref {
+        Some(node_ref) => view!(div| canvas(ref(node_ref.clone()))),
+        None => view!(div| canvas()),
+    }
+}
+
+fn node_ref_patches(patches: &[Patch<Msg>]) -> Vec<Option<NodeRef>> {
+    patches.iter().filter_map(|x| match x {
+        Patch::SetNodeRef{value, ..} => Some(value.clone()),
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn node_refs_are_patched_only_when_they_change() {
+    let dom = MockDom::new();
+    let (node_ref, other) = (NodeRef::new(), NodeRef::new());
+    assert_eq!(node_ref.clone(), node_ref);
+    assert_ne!(node_ref, other);
+    let mut active = with_ref(Some(&node_ref));
+    dom.mount(&active);
+    assert_eq!(active.patches(&mut with_ref(Some(&node_ref)), String::from("body")), Vec::new());
+    let patches = active.patches(&mut with_ref(Some(&other)), String::from("body"));
+    assert_eq!(node_ref_patches(&patches), vec![Some(other.clone())]);
+    active.apply_patches(&patches, &dom);
+    assert_eq!(active.query("canvas").and_then(|x| x.node_ref()), Some(other.clone()));
+    // DROPPED FROM THE VIEW
+    let patches = active.patches(&mut with_ref(None), String::from("body"));
+    assert_eq!(node_ref_patches(&patches), vec![None]);
+    active.apply_patches(&patches, &dom);
+    assert_eq!(active.query("canvas").and_then(|x| x.node_ref()), None);
+    // NO BROWSER ELEMENT BEHIND A MOCK NODE
+    assert_eq!(other.get(), None);
+}
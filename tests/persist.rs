@@ -0,0 +1,129 @@
+//! Persisting the model: saving after updates, restoring on start, and what
+//! happens to missing or corrupt data.
+
+use std::rc::Rc;
+use serde::{Serialize, Deserialize};
+use view_prototype::core::{Component, Process};
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+use view_prototype::persist::{MemoryStorage, Persistence, Storage};
+
+#[derive(Debug, PartialEq, Clone, Hash, Serialize, Deserialize)]
+struct Model {
+    total: u32,
+}
+
+// ONLY SENT BY THE TESTS THAT RUN WITHOUT `dom`
+#[cfg_attr(feature = "dom", allow(dead_code))]
+#[derive(Debug, PartialEq, Clone, Hash)]
+enum Msg {
+    Add(u32),
+}
+
+fn view(model: &Model) -> Html<Msg> {
+    let mut node = Html::new_node(String::from("p"));
+    node.add_child(Html::Text{value: format!("total {}", model.total)});
+    node
+}
+
+fn process() -> (Process<Model, Msg>, Rc<MockDom>) {
+    let dom = Rc::new(MockDom::new());
+    let update = |model: &mut Model, Msg::Add(n): Msg| model.total += n;
+    let process = Process::with_backend(Component::new(Model {total: 0}, update, view), dom.clone());
+    (process, dom)
+}
+
+#[test]
+fn saves_once_changes_settle() {
+    let store = MemoryStorage::new();
+    let persistence: Persistence<Model> = Persistence::new(store.clone(), "app").debounce(100.0);
+    persistence.changed(0.0);
+    assert!(!persistence.flush(50.0, &Model {total: 1}));
+    assert_eq!(store.get("app/model"), None);
+    // A LATER CHANGE RESTARTS THE WAIT
+    persistence.changed(60.0);
+    assert!(!persistence.flush(120.0, &Model {total: 2}));
+    assert!(persistence.flush(160.0, &Model {total: 2}));
+    assert_eq!(persistence.load(), Some(Model {total: 2}));
+    // NOTHING PENDING, NOTHING SAVED
+    assert!(!persistence.flush(1000.0, &Model {total: 3}));
+    assert_eq!(persistence.load(), Some(Model {total: 2}));
+}
+
+#[test]
+fn restores_the_saved_model_on_start() {
+    let store = MemoryStorage::new();
+    Persistence::new(store.clone(), "app").save(&Model {total: 7});
+    let (process, dom) = process();
+    process.persist(Persistence::new(store, "app"));
+    assert_eq!(process.model(), Model {total: 7});
+    assert!(dom.body_html().contains(">total 7</p>"), "{}", dom.body_html());
+}
+
+#[test]
+fn missing_or_corrupt_data_keeps_the_initial_model() {
+    let saved = [
+        None,
+        Some("not json"),
+        Some(r#"{"version":0,"model":{"total":"seven"}}"#),
+        Some(r#"{"model":{"total":7}}"#),
+    ];
+    for value in saved.iter() {
+        let store = MemoryStorage::new();
+        if let Some(value) = value {
+            store.set("app/model", value);
+        }
+        let (process, _) = process();
+        process.persist(Persistence::new(store, "app"));
+        assert_eq!(process.model(), Model {total: 0}, "restored from {:?}", value);
+    }
+}
+
+#[test]
+fn other_versions_need_a_migration() {
+    let store = MemoryStorage::new();
+    Persistence::new(store.clone(), "app").version(1).save(&Model {total: 7});
+    let current: Persistence<Model> = Persistence::new(store.clone(), "app").version(2);
+    assert_eq!(current.load(), None);
+    let current = current.migrate(|version, mut model| {
+        assert_eq!(version, 1);
+        model["total"] = serde_json::json!(8);
+        Some(model)
+    });
+    assert_eq!(current.load(), Some(Model {total: 8}));
+}
+
+// THE BROWSER BUILD READS ITS CLOCK THROUGH `js_sys`
+#[cfg(not(feature = "dom"))]
+#[test]
+fn saves_after_updates() {
+    let store = MemoryStorage::new();
+    let (process, _) = process();
+    process.persist(Persistence::new(store.clone(), "app").debounce(0.0));
+    process.send(Msg::Add(3));
+    process.tick();
+    assert_eq!(store.get("app/model"), None);
+    // SAVED ON THE TICK AFTER THE CHANGE SETTLES
+    process.tick();
+    let saved: Persistence<Model> = Persistence::new(store, "app");
+    assert_eq!(saved.load(), Some(Model {total: 3}));
+}
+
+// THE BROWSER BUILD READS ITS CLOCK THROUGH `js_sys`
+#[cfg(not(feature = "dom"))]
+#[test]
+fn pending_saves_are_written_on_teardown() {
+    let store = MemoryStorage::new();
+    let (process, _) = process();
+    process.persist(Persistence::new(store.clone(), "app").debounce(60_000.0));
+    assert!(!process.save_pending());
+    process.send(Msg::Add(3));
+    process.tick();
+    process.tick();
+    assert_eq!(store.get("app/model"), None);
+    // AS ON `pagehide`
+    assert!(process.save_pending());
+    let saved: Persistence<Model> = Persistence::new(store, "app");
+    assert_eq!(saved.load(), Some(Model {total: 3}));
+    assert!(!process.save_pending());
+}
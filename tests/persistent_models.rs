@@ -0,0 +1,49 @@
+//! Models built from the re-exported persistent collections: updated in
+//! place, while copies taken earlier keep their contents.
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use view_prototype::core::{Component, Process};
+use view_prototype::html::*;
+use view_prototype::mock_dom::{MockDom, Op};
+use view_prototype::rpds::Vector;
+
+type Msg = u8;
+
+/// Three rows; message `n` renames row `n % 3`.
+fn rows() -> Component<Vector<String>, Msg> {
+    let initial = (0..3).fold(Vector::new(), |rows, _| rows.push_back(String::from("empty")));
+    Component::new(
+        initial,
+        |model: &mut Vector<String>, msg: Msg| {
+            model.set_mut(usize::from(msg % 3), format!("item {}", msg));
+        },
+        |model: &Vector<String>| {
+            let rows = model.iter().map(|x| view!(li| text x)).collect();
+            view!(ul| {Html::new_fragment(rows)})
+        },
+    )
+}
+
+#[test]
+fn persistent_models_update_in_place() {
+    let dom = Rc::new(MockDom::new());
+    let process = Process::with_backend(rows(), dom.clone());
+    process.send(1);
+    process.tick();
+    let snapshot = process.model();
+    dom.take_ops();
+    process.send(5);
+    process.tick();
+    // SNAPSHOTS ARE UNTOUCHED BY LATER UPDATES
+    assert_eq!(snapshot.get(2).map(String::as_str), Some("empty"));
+    assert_eq!(process.model().get(2).map(String::as_str), Some("item 5"));
+    assert_eq!(dom.bare_body_html(), "<div><ul><li>empty</li><li>item 1</li><li>item 5</li></ul></div>");
+    // ONLY THE CHANGED ROW IS TOUCHED
+    let ops = dom.take_ops();
+    assert_eq!(ops.len(), 1, "{:?}", ops);
+    match &ops[0] {
+        Op::SetTextContent{value, ..} => assert_eq!(value, "item 5"),
+        op => panic!("unexpected {:?}", op),
+    }
+}
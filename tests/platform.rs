@@ -0,0 +1,42 @@
+//! The stand-in browser types of a build without `dom`: handlers get real
+//! event values, e.g. the text typed into an input.
+#![cfg(not(feature = "dom"))]
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use view_prototype::core::{Component, Process};
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+use view_prototype::platform::JsValue;
+
+#[test]
+fn values_keep_their_type() {
+    assert_eq!(JsValue::from_str("a").as_string(), Some(String::from("a")));
+    assert_eq!(JsValue::from(2.5).as_f64(), Some(2.5));
+    assert_eq!(JsValue::from(true).as_bool(), Some(true));
+    assert_eq!(JsValue::from("a").as_f64(), None);
+    assert!(JsValue::UNDEFINED.is_undefined());
+    assert!(JsValue::NULL.is_null());
+    assert!(!JsValue::from(false).is_null());
+}
+
+#[test]
+fn handlers_read_the_dispatched_value() {
+    let dom = Rc::new(MockDom::new());
+    let spec = Component::new(
+        String::new(),
+        |model: &mut String, msg: String| *model = msg,
+        |model: &String| view!(div|
+            input(.input(|event: JsValue| event.as_string().unwrap_or_default())),
+            p(text model)
+        ),
+    );
+    let process = Process::with_backend(spec, dom.clone());
+    let input = dom.find_by_tag("input").unwrap();
+    assert!(dom.dispatch(input, "input", JsValue::from_str("hello")));
+    process.tick();
+    assert_eq!(process.model(), "hello");
+    assert_eq!(dom.bare_body_html(), "<div><div><input></input><p>hello</p></div></div>");
+    // NOTHING LISTENS FOR OTHER EVENTS
+    assert!(!dom.dispatch(input, "change", JsValue::from_str("bye")));
+}
@@ -0,0 +1,39 @@
+//! Portals: their children render under another element, yet stay part of
+//! the view that holds them.
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use view_prototype::html::*;
+use view_prototype::mock_dom::{MockDom, MockId};
+use view_prototype::platform::JsValue;
+
+type Msg = u8;
+
+fn with_modal(modal: Option<&str>) -> Html<Msg> {
+    let mut page = view!(main| p(text "page"));
+    if let Some(title) = modal {
+        page.add_child(Html::new_portal(String::from("modals"), vec![
+            view!(h1| text title),
+            view!(button| .click(|_| 7), text "close"),
+        ]));
+    }
+    view!(div| {page})
+}
+
+#[test]
+fn portals_render_into_their_target() {
+    let dom = MockDom::new();
+    let mut active = with_modal(Some("Hello"));
+    dom.mount(&active);
+    let host = dom.find_by_id("modals").expect("missing portal target");
+    assert_eq!(dom.bare_html(host), "<div id=\"modals\"><div><h1>Hello</h1><button>close</button></div></div>");
+    let main = dom.find_by_tag("main").unwrap();
+    assert!(!dom.html(main).contains("Hello"));
+    // HANDLERS STILL REACH THE VIEW
+    assert!(dom.dispatch(dom.find_by_tag("button").unwrap(), "click", JsValue::UNDEFINED));
+    assert_eq!(active.tick(), vec![7]);
+    active.sync(&mut with_modal(Some("Bye")), String::from("body"), &dom);
+    assert!(dom.html(host).contains(">Bye</h1>"), "{}", dom.html(host));
+    active.sync(&mut with_modal(None), String::from("body"), &dom);
+    assert_eq!(dom.node(host).children, Vec::<MockId>::new());
+}
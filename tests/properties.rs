@@ -0,0 +1,81 @@
+//! DOM properties: set on the live element, patched only when they change
+//! and reset once the view drops them.
+#![cfg(feature = "ssr")]
+
+#[macro_use] extern crate view_prototype;
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+use view_prototype::sync::Patch;
+
+type Msg = u8;
+
+fn input(attributes: Vec<Attribute>) -> Html<Msg> {
+    let mut node = Html::new_node(String::from("input"));
+    for attribute in attributes {
+        node.add_attribute(attribute);
+    }
+    view!(div| {node})
+}
+
+fn property(key: &str, value: PropertyValue) -> Attribute {
+    Attribute::Property{key: key.to_owned(), value}
+}
+
+#[test]
+fn properties_are_patched_only_when_they_change() {
+    let dom = MockDom::new();
+    let value = |x: &str| property("value", PropertyValue::from(x));
+    let mut active = input(vec![value("a"), property("checked", PropertyValue::from(true))]);
+    dom.mount(&active);
+    let patches = active.patches(&mut input(vec![value("a"), property("checked", PropertyValue::from(true))]), String::from("body"));
+    assert_eq!(patches, Vec::new());
+    let patches = active.patches(&mut input(vec![value("b"), property("checked", PropertyValue::from(true))]), String::from("body"));
+    match patches.as_slice() {
+        [Patch::SetProperty{key, value, ..}] => {
+            assert_eq!((key.as_str(), value), ("value", &PropertyValue::from("b")));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    active.apply_patches(&patches, &dom);
+    let node = dom.find_by_tag("input").unwrap();
+    assert_eq!(dom.node(node).properties.get("value"), Some(&PropertyValue::from("b")));
+    // THE VNODE NOW HOLDS THE NEW VALUE, SO THE SAME VIEW PATCHES NOTHING
+    let patches = active.patches(&mut input(vec![value("b"), property("checked", PropertyValue::from(true))]), String::from("body"));
+    assert_eq!(patches, Vec::new());
+}
+
+#[test]
+fn removed_properties_are_reset() {
+    let dom = MockDom::new();
+    let mut active = input(vec![
+        property("value", PropertyValue::from("typed")),
+        property("checked", PropertyValue::from(true)),
+        property("valueAsNumber", PropertyValue::Number(3.0)),
+    ]);
+    dom.mount(&active);
+    let node = dom.find_by_tag("input").unwrap();
+    dom.take_ops();
+    active.sync(&mut input(Vec::new()), String::from("body"), &dom);
+    let properties = dom.node(node).properties;
+    assert_eq!(properties.get("value"), Some(&PropertyValue::from("")));
+    assert_eq!(properties.get("checked"), Some(&PropertyValue::from(false)));
+    assert_eq!(properties.get("valueAsNumber"), Some(&PropertyValue::Number(0.0)));
+    assert_eq!(dom.take_ops().len(), 3);
+    // ONCE RESET, THEY ARE GONE FROM THE VNODE TOO
+    active.sync(&mut input(Vec::new()), String::from("body"), &dom);
+    assert_eq!(dom.take_ops(), Vec::new());
+}
+
+#[test]
+fn attribute_replaced_by_a_property_is_removed() {
+    let dom = MockDom::new();
+    let mut active = input(vec![Attribute::Pair{key: String::from("value"), value: String::from("a")}]);
+    dom.mount(&active);
+    let node = dom.find_by_tag("input").unwrap();
+    assert_eq!(dom.node(node).attributes.get("value").map(|x| x.as_str()), Some("a"));
+    active.sync(&mut input(vec![property("value", PropertyValue::from("b"))]), String::from("body"), &dom);
+    let mock = dom.node(node);
+    assert_eq!(mock.attributes.get("value"), None);
+    assert_eq!(mock.properties.get("value"), Some(&PropertyValue::from("b")));
+    assert_eq!(active.to_html_string(), input(vec![property("value", PropertyValue::from("b"))]).to_html_string());
+}
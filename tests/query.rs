@@ -0,0 +1,113 @@
+//! Tests for selector queries over `Html`.
+#![cfg(feature = "ssr")]
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use view_prototype::css::CssValue;
+use view_prototype::core::Component;
+use view_prototype::html::*;
+use view_prototype::query::Selector;
+use view_prototype::testing::{Find, Harness};
+
+#[derive(Debug, PartialEq, Clone, Hash)]
+enum Msg {
+    Save,
+    Cancel,
+    Email(String),
+}
+
+fn form(items: &[&str]) -> Html<Msg> {
+    let items: Vec<Html<Msg>> = items
+        .iter()
+        .map(|x| view!(li| class = "item done", text *x))
+        .collect();
+    view!(form|
+        class = "signup",
+        ul({Html::new_fragment(items)}),
+        label(
+            text "Email",
+            input(
+                type = "email",
+                name = "email",
+                required = true,
+                @value = "me@example.com",
+                color: "red",
+                .input(|_| Msg::Email(String::new())),
+                .change(|_| Msg::Email(String::new()))
+            )
+        ),
+        button(type = "button", .click(|_| Msg::Cancel), text "Cancel"),
+        button(type = "submit", .click(|_| Msg::Save), span(text " Save "))
+    )
+}
+
+#[test]
+fn finds_elements_by_tag_text_and_attribute() {
+    let html = form(&["a", "b", "c"]);
+    let items = html.query_all("li");
+    assert_eq!(
+        items.iter().map(|x| x.text_content()).collect::<Vec<_>>(),
+        vec!["a", "b", "c"],
+    );
+    let save = html.query("button:text('Save')").expect("no save button");
+    assert_eq!(save.attribute("type"), Some(String::from("submit")));
+    assert_eq!(save.event_names(), vec!["click"]);
+    let email = html.query("input[name=\"email\"]").expect("no email input");
+    assert_eq!(email.attribute("required"), Some(String::new()));
+    assert_eq!(email.attribute("value"), None);
+    assert_eq!(email.event_names(), vec!["change", "input"]);
+    assert_eq!(email.styling().len(), 1);
+}
+
+#[test]
+fn combinators_and_attribute_operators() {
+    let html = form(&["a", "b"]);
+    assert_eq!(html.query_all("form li").len(), 2);
+    assert_eq!(html.query_all("form > li").len(), 0);
+    assert_eq!(html.query_all("form > ul > li.done").len(), 2);
+    assert_eq!(html.query_all(".signup label > input").len(), 1);
+    assert_eq!(html.query_all("button[type^=sub], li").len(), 3);
+    assert_eq!(html.query_all("[class~=done]").len(), 2);
+    assert_eq!(html.query_all("[type$=mit]").len(), 1);
+    assert_eq!(html.query_all("[name*=mai]").len(), 1);
+    assert_eq!(html.query_all("*:contains('Save')").len(), 3);
+    assert_eq!(html.query_all("button:contains(Cancel)").len(), 1);
+    assert!(html.query("li.missing").is_none());
+    assert_eq!(html.query("form").map(|x| x.id()), Some(html.id()));
+}
+
+#[test]
+fn find_all_takes_a_predicate() {
+    let html = form(&["a"]);
+    let handled = html.find_all(|node| !node.event_names().is_empty());
+    assert_eq!(handled.len(), 3);
+}
+
+#[test]
+fn invalid_selectors_report_a_position() {
+    let err = Selector::parse("li[name=").unwrap_err();
+    assert_eq!(err.position, 8);
+    assert!(Selector::parse("#main").is_err());
+    assert!(Selector::parse("li:hover(x)").is_err());
+    assert!(Selector::parse("ul >").is_err());
+    assert!(Selector::parse("ul, ").is_err());
+}
+
+#[test]
+#[should_panic(expected = "invalid selector")]
+fn query_panics_on_an_invalid_selector() {
+    form(&[]).query_all("li[");
+}
+
+#[test]
+fn harness_dispatches_by_selector() {
+    let spec = Component::new(
+        Vec::<Msg>::new(),
+        |log: &mut Vec<Msg>, msg: Msg| log.push(msg),
+        |_: &Vec<Msg>| form(&["a"]),
+    );
+    let mut harness = Harness::new(spec);
+    harness.dispatch(Find::selector("button:text(Save)"), "click");
+    harness.dispatch(Find::selector("label > [name=email]"), "change");
+    assert_eq!(harness.model(), vec![Msg::Save, Msg::Email(String::new())]);
+}
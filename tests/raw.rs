@@ -0,0 +1,32 @@
+//! Raw markup nodes: sanitized before they reach the document, and left
+//! alone while their markup is unchanged.
+
+#[macro_use] extern crate view_prototype;
+use view_prototype::html::*;
+use view_prototype::mock_dom::{MockDom, Op};
+use view_prototype::sanitize::Sanitizer;
+
+type Msg = u8;
+
+fn comment_body(markup: &str) -> Html<Msg> {
+    view!(article| {Html::new_sanitized_raw(markup, &Sanitizer::new())})
+}
+
+#[test]
+fn sanitized_markup_reaches_the_document_clean() {
+    let dom = MockDom::new();
+    let mut active = comment_body("<p onclick=\"steal()\">hi <a href=\"javascript:alert(1)\">x</a></p><script>alert(1)</script>");
+    dom.mount(&active);
+    let raw: Vec<String> = dom.ops().into_iter().filter_map(|x| match x {
+        Op::CreateRaw{markup, ..} => Some(markup),
+        _ => None,
+    }).collect();
+    assert_eq!(raw, vec![String::from("<p>hi <a>x</a></p>")]);
+    assert_eq!(dom.bare_body_html(), "<article><p>hi <a>x</a></p></article>");
+    // UNCHANGED MARKUP IS LEFT ALONE
+    dom.take_ops();
+    active.sync(&mut comment_body("<p>hi <a>x</a></p>"), String::from("body"), &dom);
+    assert_eq!(dom.take_ops(), Vec::new());
+    active.sync(&mut comment_body("<em>bye</em><img src=x onerror=alert(1)>"), String::from("body"), &dom);
+    assert_eq!(dom.bare_body_html(), "<article><em>bye</em><img src=\"x\"></article>");
+}
@@ -0,0 +1,57 @@
+//! Skipped renders: `view` and `sync` only run for a model that changed.
+
+#[macro_use] extern crate view_prototype;
+use std::cell::Cell;
+use std::rc::Rc;
+use view_prototype::core::{Component, Process};
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+
+type Msg = u32;
+
+/// Sets the model to each message and counts the views built.
+fn counter(views: Rc<Cell<usize>>) -> Component<u32, Msg> {
+    Component::new(
+        0,
+        |model: &mut u32, msg: Msg| *model = msg,
+        move |model: &u32| {
+            views.set(views.get() + 1);
+            view!(p| text (model.to_string()))
+        },
+    )
+}
+
+#[test]
+fn views_are_built_only_for_changed_models() {
+    let views = Rc::new(Cell::new(0));
+    let dom = Rc::new(MockDom::new());
+    let process = Process::with_backend(counter(views.clone()), dom.clone());
+    assert_eq!(views.get(), 1);
+    dom.take_ops();
+    // NO MESSAGES
+    process.tick();
+    assert_eq!(views.get(), 1);
+    // A MESSAGE THAT LEAVES THE MODEL EQUAL
+    process.send(0);
+    process.tick();
+    assert_eq!(views.get(), 1);
+    assert_eq!(dom.take_ops(), Vec::new());
+    process.send(3);
+    process.tick();
+    assert_eq!(views.get(), 2);
+    assert_eq!(dom.bare_body_html(), "<div><p>3</p></div>");
+}
+
+#[test]
+fn jumping_to_the_shown_state_builds_nothing() {
+    let views = Rc::new(Cell::new(0));
+    let process = Process::with_backend(counter(views.clone()), Rc::new(MockDom::new()));
+    process.start_debugger();
+    process.send(3);
+    process.tick();
+    assert_eq!(views.get(), 2);
+    assert!(process.jump_to(1));
+    assert_eq!(views.get(), 2);
+    assert!(process.jump_to(0));
+    assert_eq!(views.get(), 3);
+}
@@ -0,0 +1,85 @@
+//! The allow-list sanitizer, and the ways script URLs try to get past it.
+use view_prototype::sanitize::Sanitizer;
+
+fn sanitize(markup: &str) -> String {
+    Sanitizer::new().sanitize(markup)
+}
+
+#[test]
+fn unwraps_unknown_tags_and_drops_scripts() {
+    assert_eq!(
+        sanitize("<p onclick=\"x()\" class=a><blink>hi</blink><script>alert(1)</script></p>"),
+        "<p class=\"a\">hi</p>",
+    );
+}
+
+#[test]
+fn keeps_allowed_and_relative_urls() {
+    let allowed = [
+        "https://example.com/a?b=1&c=2",
+        "http://example.com",
+        "mailto:someone@example.com",
+        "/docs/page",
+        "page.html#top",
+        "?q=1",
+        "//example.com/x",
+        "HTTPS://EXAMPLE.COM",
+        "&#104;ttps://example.com",
+    ];
+    for url in allowed.iter() {
+        let markup = format!("<a href=\"{}\">x</a>", url);
+        assert!(sanitize(&markup).contains("href="), "dropped {}", url);
+    }
+}
+
+#[test]
+fn drops_urls_with_other_schemes() {
+    let blocked = [
+        "javascript:alert(1)",
+        "JaVaScRiPt:alert(1)",
+        " javascript:alert(1)",
+        "vbscript:msgbox(1)",
+        "data:text/html,<script>alert(1)</script>",
+        "ftp://example.com",
+    ];
+    for url in blocked.iter() {
+        let markup = format!("<a href=\"{}\">x</a>", url);
+        assert_eq!(sanitize(&markup), "<a>x</a>", "kept {}", url);
+    }
+    let with_tel = Sanitizer::new().allow_scheme("tel:");
+    assert!(with_tel.sanitize("<a href=\"tel:+123\">x</a>").contains("href="));
+}
+
+#[test]
+fn decodes_references_as_the_browser_does() {
+    let bypasses = [
+        // NUMERIC, WITHOUT THE `;`
+        "&#106avascript:alert(1)",
+        "&#x6Aavascript:alert(1)",
+        // ZERO-PADDED, ANY LENGTH
+        "&#0000000000000106;avascript:alert(1)",
+        "&#x000000000000006A;avascript:alert(1)",
+        // NAMED, CASE-SENSITIVE
+        "java&Tab;script:alert(1)",
+        "java&NewLine;script:alert(1)",
+        "javascript&colon;alert(1)",
+        // CONTROL CHARACTERS AND WHITESPACE INSIDE THE SCHEME
+        "java&#9;script:alert(1)",
+        "java\tscript:alert(1)",
+        "&#1;javascript:alert(1)",
+        // A REFERENCE WE DON'T KNOW MIGHT STILL BE A `:`
+        "javascript&unknown;alert(1)",
+    ];
+    for url in bypasses.iter() {
+        let markup = format!("<a href=\"{}\">x</a>", url);
+        assert_eq!(sanitize(&markup), "<a>x</a>", "kept {}", url);
+    }
+}
+
+#[test]
+fn escapes_kept_attribute_values() {
+    assert_eq!(
+        sanitize("<p title='a \"b\" <c>'>x</p>"),
+        "<p title=\"a &quot;b&quot; &lt;c&gt;\">x</p>",
+    );
+}
@@ -0,0 +1,152 @@
+//! Round-trips of `Html` and `Patch` through JSON and bincode.
+#![cfg(feature = "ssr")]
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use view_prototype::css::CssValue;
+use view_prototype::html::*;
+use view_prototype::sync::Patch;
+use view_prototype::platform::JsValue;
+
+#[derive(Debug, PartialEq, Clone, Hash, Serialize, Deserialize)]
+enum Msg {
+    Toggle(u32),
+    Edit(String),
+    Clear,
+}
+
+fn row(todo: &(u32, &'static str)) -> Html<Msg> {
+    let (id, title) = *todo;
+    view!(li|
+        class = "todo",
+        :hover (
+            background_color: "yellow"
+        ),
+        input(type = "checkbox", checked = true, .click[Msg::Toggle(id)]),
+        span(text title)
+    )
+}
+
+fn view() -> Html<Msg> {
+    view!(
+        display: "flex",
+        svg(viewBox = "0 0 8 8", "xlink:href" = "#dot", circle(r = "4")),
+        input(@value = "draft", .input(|_| Msg::Edit(String::new()))),
+        button(.click[Msg::Clear], text "Clear"),
+        ul({lazy((1, "milk"), row)}, {lazy((2, "eggs"), row)}),
+        {Html::new_unmanaged(String::from("div"), String::from("map"))},
+        {Html::new_raw(String::from("<hr>"))},
+        {Html::placeholder()}
+    )
+}
+
+fn handler<'a>(html: &'a Html<Msg>, selector: &str, event_name: &str) -> &'a Handler<Msg> {
+    html.query(selector)
+        .and_then(|node| node.events())
+        .and_then(|events| events.get(event_name))
+        .expect("missing handler")
+}
+
+#[test]
+fn html_round_trips_through_json() {
+    // UNFORCED LAZY NODES ARE BUILT AS THEY ARE WRITTEN
+    let mut html = view();
+    let json = serde_json::to_string(&html).unwrap();
+    let decoded: Html<Msg> = serde_json::from_str(&json).unwrap();
+    html.force_all();
+    assert_eq!(decoded.to_html_string(), html.to_html_string());
+    let json = serde_json::to_string(&html).unwrap();
+    let decoded: Html<Msg> = serde_json::from_str(&json).unwrap();
+    // LAZY VIEWS COME BACK AS NEW CLOSURES, SO COMPARE WHAT IS WRITTEN
+    assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+    assert_eq!(decoded.query("li").and_then(|x| x.id()), html.query("li").and_then(|x| x.id()));
+}
+
+#[test]
+fn html_round_trips_through_bincode() {
+    let mut html = view();
+    html.force_all();
+    let bytes = bincode::serialize(&html).unwrap();
+    let decoded: Html<Msg> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(bincode::serialize(&decoded).unwrap(), bytes);
+    assert_eq!(decoded.to_pretty_string(), html.to_pretty_string());
+}
+
+#[test]
+fn message_handlers_keep_their_message() {
+    let json = serde_json::to_string(&view()).unwrap();
+    let decoded: Html<Msg> = serde_json::from_str(&json).unwrap();
+    let clear = handler(&decoded, "button", "click");
+    assert_eq!(clear.message, Some(Msg::Clear));
+    assert_eq!(clear.eval(JsValue::UNDEFINED), Some(Msg::Clear));
+    let toggle = handler(&decoded, "li input", "click");
+    assert_eq!(toggle.eval(JsValue::UNDEFINED), Some(Msg::Toggle(1)));
+}
+
+#[test]
+fn closure_handlers_come_back_detached() {
+    let json = serde_json::to_string(&view()).unwrap();
+    let decoded: Html<Msg> = serde_json::from_str(&json).unwrap();
+    let edit = handler(&decoded, "div > input", "input");
+    assert_eq!(edit.message, None);
+    assert!(edit.is_detached());
+    assert_eq!(edit.eval(JsValue::UNDEFINED), None);
+    // FIRING IT SENDS NOTHING
+    if let Some(Html::Node{mailbox, ..}) = decoded.query("div > input") {
+        mailbox.insert(String::from("input"), JsValue::UNDEFINED);
+    }
+    assert_eq!(decoded.tick(), Vec::<Msg>::new());
+}
+
+#[test]
+fn refs_and_hooks_are_dropped() {
+    let html: Html<Msg> = view!(div(ref(NodeRef::new()), on_mount(|_| Some(Msg::Clear))));
+    let json = serde_json::to_string(&html).unwrap();
+    let decoded: Html<Msg> = serde_json::from_str(&json).unwrap();
+    let div = &decoded.children()[0];
+    assert_eq!(div.node_ref(), None);
+    assert!(div.lifecycle().unwrap().is_empty());
+}
+
+#[test]
+fn patches_round_trip() {
+    let mut html = view();
+    html.force_all();
+    let mut events: BTreeMap<String, Handler<Msg>> = BTreeMap::new();
+    events.insert(String::from("click"), Handler::message(Msg::Toggle(7)));
+    let patches: Vec<Patch<Msg>> = vec![
+        Patch::SetChildText {parent_id: String::from("_1"), value: String::from("hi")},
+        Patch::SetNode {id: String::from("_2"), value: html},
+        Patch::SetAttributes {id: String::from("_3"), value: vec![
+            Attribute::Pair {key: String::from("class"), value: String::from("x")},
+            Attribute::Toggle {key: String::from("hidden"), value: true},
+        ]},
+        Patch::SetStyling {id: String::from("_3"), value: vec![
+            Style::PseudoClass(String::from("hover"), vec![]),
+        ]},
+        Patch::SetEvents {id: String::from("_4"), value: events},
+        Patch::SetProperty {id: String::from("_5"), key: String::from("value"), value: PropertyValue::Number(1.5)},
+        Patch::SetNodeRef {id: String::from("_6"), value: None},
+        Patch::SetLifecycle {id: String::from("_7"), value: Lifecycle::new()},
+    ];
+    let bytes = bincode::serialize(&patches).unwrap();
+    let decoded: Vec<Patch<Msg>> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(bincode::serialize(&decoded).unwrap(), bytes);
+    let json = serde_json::to_string(&patches).unwrap();
+    let decoded: Vec<Patch<Msg>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+    match &decoded[4] {
+        Patch::SetEvents {value, ..} => {
+            assert_eq!(value["click"].eval(JsValue::UNDEFINED), Some(Msg::Toggle(7)));
+        }
+        patch => panic!("unexpected {:?}", patch),
+    }
+}
+
+#[test]
+fn lazy_nodes_without_contents_are_rejected() {
+    let error = serde_json::from_str::<Html<Msg>>(r#"{"Lazy":[1,[]]}"#).unwrap_err();
+    assert!(error.to_string().contains("invalid length 0"), "{}", error);
+}
@@ -0,0 +1,97 @@
+//! Snapshot tests for `Html::to_pretty_string`.
+#![cfg(feature = "ssr")]
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use view_prototype::html::*;
+use view_prototype::css::CssValue;
+use view_prototype::testing;
+
+#[derive(Debug, PartialEq, Clone, Hash)]
+enum Msg {
+    Toggle(u32),
+    Edit(String),
+}
+
+struct Todo {
+    id: u32,
+    title: &'static str,
+    done: bool,
+}
+
+fn row(todo: &(u32, &'static str, bool)) -> Html<Msg> {
+    let (id, title, done) = *todo;
+    view!(li|
+        class = "todo",
+        color: if done {"gray"} else {"black"},
+        :hover (
+            background_color: "yellow"
+        ),
+        input(type = "checkbox", @checked = done, .click(move |_| Msg::Toggle(id))),
+        span(text title)
+    )
+}
+
+fn view(todos: &[Todo], draft: &str) -> Html<Msg> {
+    let draft = draft.to_owned();
+    let icon: Html<Msg> = view!(svg| viewBox = "0 0 8 8", circle(r = "4"));
+    view!(
+        display: "flex",
+        flex_direction: "column",
+        header(
+            {icon},
+            h1(text "todos"),
+            input(
+                placeholder = "What needs to be done?",
+                @value = draft.as_str(),
+                .input(|_| Msg::Edit(String::new())),
+                .change(|_| Msg::Edit(String::new())),
+                ref(NodeRef::new())
+            )
+        ),
+        ul({Html::new_fragment(todos.iter().map(|x| lazy((x.id, x.title, x.done), row)).collect())}),
+        {Html::new_unmanaged(String::from("div"), String::from("map"))},
+        {Html::placeholder()},
+        {Html::new_raw(String::from("<hr>"))}
+    )
+}
+
+#[test]
+fn todo_list() {
+    let todos = vec![
+        Todo {id: 1, title: "write snapshots", done: true},
+        Todo {id: 2, title: "review \"diffs\"", done: false},
+    ];
+    assert_snapshot!("todo_list", view(&todos, "milk"));
+}
+
+#[test]
+fn pretty_string_leaves_out_ids() {
+    let a: Html<Msg> = view!(span(text "x"));
+    let b: Html<Msg> = view!(span(text "x"));
+    assert_ne!(a.id(), b.id());
+    assert_eq!(a.to_pretty_string(), b.to_pretty_string());
+}
+
+#[test]
+#[should_panic(expected = "-       \"a\"\n+       \"b\"")]
+fn mismatch_shows_a_line_diff() {
+    let path = std::env::temp_dir().join(format!("view-prototype-{}.snap", std::process::id()));
+    let old: Html<Msg> = view!(ul(li(text "a")));
+    let new: Html<Msg> = view!(ul(li(text "b")));
+    let new = new.to_pretty_string();
+    std::fs::write(&path, old.to_pretty_string()).unwrap();
+    let result = std::panic::catch_unwind(|| testing::assert_snapshot(&path, &new));
+    std::fs::remove_file(&path).ok();
+    if let Err(err) = result {
+        std::panic::resume_unwind(err);
+    }
+}
+
+#[test]
+#[should_panic(expected = "is missing")]
+fn missing_snapshots_fail() {
+    let path = std::env::temp_dir().join(format!("view-prototype-missing-{}.snap", std::process::id()));
+    let html: Html<Msg> = view!(ul(li(text "a")));
+    testing::assert_snapshot(&path, &html.to_pretty_string());
+}
@@ -0,0 +1,199 @@
+//! Tests for parsing HTML templates into views.
+#![cfg(feature = "ssr")]
+
+#[macro_use] extern crate view_prototype;
+use std::rc::Rc;
+use view_prototype::css::CssValue;
+use view_prototype::core::{Component, Process};
+use view_prototype::html::*;
+use view_prototype::mock_dom::{MockDom, Op};
+use view_prototype::platform::JsValue;
+use view_prototype::template::{self, Bindings, TemplateError};
+use view_prototype::testing::{Find, Harness};
+
+#[derive(Debug, PartialEq, Clone, Hash)]
+enum Msg {
+    Save,
+    Edit(String),
+}
+
+fn parse(source: &str) -> Html<Msg> {
+    template::parse(source, &Bindings::new()).unwrap_or_else(|err| panic!("{}", err))
+}
+
+fn error(source: &str, bindings: &Bindings<Msg>) -> TemplateError {
+    template::parse(source, bindings).expect_err("expected a parse error")
+}
+
+#[test]
+fn matches_the_same_view_built_with_the_macro() {
+    let parsed = parse(r#"
+        <!DOCTYPE html>
+        <section class="card" style="display: flex; background-color: #eee">
+            <h1 title='Tom &amp; Jerry'>Tom &amp; Jerry &#x2764;</h1>
+            <input type=text disabled>
+            <br/>
+            <!-- footer -->
+            <svg viewBox="0 0 8 8"><circle r="4"/></svg>
+        </section>
+    "#);
+    let built: Html<Msg> = view!(section|
+        class = "card",
+        display: "flex",
+        background_color: "#eee",
+        h1(title = "Tom & Jerry", text "Tom & Jerry \u{2764}"),
+        input(type = "text", disabled = true),
+        br(),
+        {Html::new_comment(String::from(" footer "))},
+        svg(viewBox = "0 0 8 8", circle(r = "4"))
+    );
+    assert_eq!(parsed.to_pretty_string(), built.to_pretty_string());
+    assert_eq!(parsed.to_html_string(), built.to_html_string());
+    let circle = parsed.query("circle").unwrap();
+    match circle {
+        Html::Node{namespace, ..} => assert_eq!(namespace, &Namespace::Svg),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn several_top_level_nodes_make_a_fragment() {
+    let parsed = parse("<p>a</p> text <p>b</p>");
+    assert_eq!(parsed.children().len(), 3);
+    match parsed {
+        Html::Fragment{..} => (),
+        other => panic!("expected a fragment, got {:?}", other),
+    }
+}
+
+#[test]
+fn script_and_style_contents_are_raw() {
+    let parsed = parse("<style>p > a { color: red }</style>");
+    assert_eq!(parsed.to_html_string(), "<style>p > a { color: red }</style>");
+}
+
+#[test]
+fn tag_names_are_lowercased_outside_svg() {
+    let parsed = parse("<DIV><Svg><foreignObject><P>x</p></foreignobject></svg></div>");
+    assert_eq!(parsed.to_html_string(), "<div><svg><foreignObject><p>x</p></foreignObject></svg></div>");
+}
+
+#[test]
+fn unquoted_values_end_at_whitespace_or_the_tag_end() {
+    let parsed = parse("<a href=/docs/intro title=a&amp;b>&#65</a>");
+    assert_eq!(parsed.attributes(), &vec![
+        Attribute::Pair{key: String::from("href"), value: String::from("/docs/intro")},
+        Attribute::Pair{key: String::from("title"), value: String::from("a&b")},
+    ]);
+    assert_eq!(parsed.text_content(), "A");
+}
+
+#[test]
+fn placeholders_bind_handlers_and_slots() {
+    let bindings = Bindings::new()
+        .message("save", Msg::Save)
+        .handler("edit", |_| Msg::Edit(String::from("typed")))
+        .slot("items", || Html::new_fragment(vec![view!(li| text "a"), view!(li| text "b")]));
+    let parsed = template::parse(r#"
+        <form>
+            <input on:input="{{ edit }}">
+            <ul>{{items}}</ul>
+            <p>Total: {{items}}!</p>
+            <button on:click="{{save}}">Save</button>
+        </form>
+    "#, &bindings).unwrap();
+    assert_eq!(parsed.query_all("ul > li").len(), 2);
+    assert_eq!(parsed.query("p").unwrap().text_content(), "Total: ab!");
+    // EACH USE OF A SLOT GETS ITS OWN IDS
+    let ids: Vec<Option<String>> = parsed.query_all("li").iter().map(|x| x.id()).collect();
+    assert_eq!(ids.len(), 4);
+    assert!(ids.iter().enumerate().all(|(ix, id)| !ids[..ix].contains(id)));
+    let save = &parsed.query("button").unwrap().events().unwrap()["click"];
+    assert_eq!(save.message, Some(Msg::Save));
+    let edit = &parsed.query("input").unwrap().events().unwrap()["input"];
+    assert_eq!(edit.eval(JsValue::UNDEFINED), Some(Msg::Edit(String::from("typed"))));
+}
+
+#[test]
+fn errors_carry_line_and_column() {
+    let bindings = Bindings::new().message("save", Msg::Save);
+    let err = error("<div>\n  <p>text</span>\n</div>", &bindings);
+    assert_eq!((err.line, err.column), (2, 10));
+    assert_eq!(err.to_string(), "line 2, column 10: expected </p>, found </span>");
+    let err = error("<ul>\n  <li>one\n", &bindings);
+    assert_eq!((err.line, err.column, err.message.as_str()), (2, 3, "unclosed <li>"));
+    let err = error("<p>\n  {{missing}}</p>", &bindings);
+    assert_eq!((err.line, err.column), (2, 3));
+    assert!(err.message.contains("no slot"), "{}", err);
+    let err = error("<button on:click=\"{{nope}}\">x</button>", &bindings);
+    assert_eq!((err.line, err.column), (1, 18));
+    let err = error("<a href=\"{{url}}\">x</a>", &bindings);
+    assert!(err.message.contains("only supported for on:"), "{}", err);
+    let err = error("<p style=\"color\">x</p>", &bindings);
+    assert!(err.message.contains("property: value"), "{}", err);
+    let err = error("</p>", &bindings);
+    assert_eq!(err.message, "unexpected </p>");
+    let err = error("<p>é {{x</p>", &bindings);
+    assert_eq!((err.line, err.column), (1, 8));
+}
+
+#[test]
+fn escaped_content_stays_escaped_when_rendered() {
+    let parsed = parse(r#"<p title="&quot; onmouseover=&quot;alert(1)">&lt;img src=x onerror=alert(1)&gt; &amp;amp;</p>"#);
+    assert_eq!(parsed.text_content(), "<img src=x onerror=alert(1)> &amp;");
+    assert_eq!(
+        parsed.to_html_string(),
+        "<p title=\"&quot; onmouseover=&quot;alert(1)\">&lt;img src=x onerror=alert(1)&gt; &amp;amp;</p>",
+    );
+    // RENDERED MARKUP PARSES BACK TO THE SAME VIEW
+    let reparsed = parse(&parsed.to_html_string());
+    assert_eq!(reparsed.to_pretty_string(), parsed.to_pretty_string());
+}
+
+#[test]
+fn backslash_writes_a_literal_placeholder() {
+    let bindings: Bindings<Msg> = Bindings::new().slot("name", || Html::Text{value: String::from("Tom")});
+    let parsed = template::parse(r#"<p title="\{{x}}">\{{name}} is {{name}}</p>"#, &bindings).unwrap();
+    assert_eq!(parsed.text_content(), "{{name}} is Tom");
+    assert_eq!(parsed.attribute("title"), Some(String::from("{{x}}")));
+}
+
+fn counter(count: &u32) -> Html<Msg> {
+    let count = *count;
+    let bindings = Bindings::new()
+        .message("save", Msg::Save)
+        .slot("count", move || Html::Text{value: count.to_string()});
+    template::parse(r#"
+        <div class="counter">
+            <span style="color: red">{{count}}</span>
+            <button on:click="{{save}}">Save</button>
+        </div>
+    "#, &bindings).unwrap()
+}
+
+#[test]
+fn parsed_views_are_diffed_and_patched() {
+    let spec = Component::new(0u32, |count: &mut u32, _: Msg| *count += 1, counter);
+    let dom = Rc::new(MockDom::new());
+    let process = Process::with_backend(spec, dom.clone());
+    dom.take_ops();
+    let button = dom.find_by_tag("button").unwrap();
+    dom.dispatch(button, "click", JsValue::UNDEFINED);
+    process.tick();
+    assert_eq!(process.model(), 1);
+    let span = dom.find_by_tag("span").unwrap();
+    assert!(dom.html(span).contains(">1</span>"), "{}", dom.html(span));
+    // ONLY THE TEXT CHANGED; NOTHING WAS REBUILT
+    let ops = dom.take_ops();
+    assert!(ops.iter().any(|op| matches!(op, Op::SetTextContent{value, ..} if value == "1")), "{:?}", ops);
+    assert!(!ops.iter().any(|op| matches!(op, Op::CreateElement{..})), "{:?}", ops);
+}
+
+#[test]
+fn harness_dispatches_to_parsed_views() {
+    let spec = Component::new(0u32, |count: &mut u32, _: Msg| *count += 1, counter);
+    let mut harness = Harness::new(spec);
+    harness.dispatch(Find::selector("button:text(Save)"), "click");
+    harness.dispatch(Find::text("Save"), "click");
+    assert_eq!(harness.model(), 2);
+}
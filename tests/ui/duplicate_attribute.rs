@@ -0,0 +1,8 @@
+use view_prototype::html;
+use view_prototype::html::Html;
+
+fn main() {
+    let _: Html<()> = html! {
+        <p class="a" title="b" class="c">"text"</p>
+    };
+}
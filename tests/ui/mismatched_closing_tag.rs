@@ -0,0 +1,8 @@
+use view_prototype::html;
+use view_prototype::html::Html;
+
+fn main() {
+    let _: Html<()> = html! {
+        <ul><li>"one"</ul></li>
+    };
+}
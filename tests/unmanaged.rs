@@ -0,0 +1,51 @@
+//! Unmanaged containers: paired by key, and moved rather than rebuilt, so
+//! third-party content inside them survives a patch.
+
+use view_prototype::backend::{Backend, LiveNode};
+use view_prototype::html::*;
+use view_prototype::mock_dom::MockDom;
+
+type Msg = u8;
+
+fn widgets(items: &[(&str, &str)]) -> Html<Msg> {
+    let mut root = Html::new_node(String::from("div"));
+    for (tag, key) in items {
+        root.add_child(Html::new_unmanaged(tag.to_string(), key.to_string()));
+    }
+    root
+}
+
+#[test]
+fn unmanaged_nodes_keep_their_dom_when_moved() {
+    let dom = MockDom::new();
+    let mut active = widgets(&[("div", "map"), ("div", "chart")]);
+    dom.mount(&active);
+    let map = dom.find_by_id(&active.children()[0].id().unwrap()).unwrap();
+    let chart = dom.find_by_id(&active.children()[1].id().unwrap()).unwrap();
+    // THIRD-PARTY CONTENT THE PATCHER MUST NOT TOUCH
+    let tiles = dom.create_element("canvas", None);
+    dom.append_child(&LiveNode::new(map), &tiles);
+    active.sync(&mut widgets(&[("div", "chart"), ("div", "map")]), String::from("body"), &dom);
+    let root = dom.find_by_id(&active.id().unwrap()).unwrap();
+    assert_eq!(dom.node(root).children, vec![chart, map]);
+    assert_eq!(dom.node(map).children.len(), 1);
+}
+
+#[test]
+fn unmanaged_nodes_are_paired_by_key() {
+    let dom = MockDom::new();
+    let mut active = widgets(&[("div", "a"), ("div", "b")]);
+    dom.mount(&active);
+    let b = dom.find_by_id(&active.children()[1].id().unwrap()).unwrap();
+    // `a` CHANGES TAG, SO IT IS REBUILT; `b` ONLY MOVES
+    active.sync(&mut widgets(&[("div", "b"), ("span", "a")]), String::from("body"), &dom);
+    let ids: Vec<String> = active.children().iter().filter_map(|x| x.id()).collect();
+    assert_eq!(dom.find_by_id(&ids[0]), Some(b));
+    let a = dom.find_by_id(&ids[1]).unwrap();
+    assert_eq!(dom.find_by_tag("span"), Some(a));
+    // ONE RULE EACH, NONE LEFT BEHIND
+    for id in ids.iter() {
+        assert_eq!(dom.rules().iter().filter(|x| Style::is_rule_for(x, id)).count(), 1, "{:?}", dom.rules());
+    }
+}
+